@@ -0,0 +1,267 @@
+use super::*;
+#[cfg(unix)]
+use alloc::ffi::CString;
+#[cfg(target_os = "windows")]
+use alloc::vec::Vec;
+#[cfg(unix)]
+use core::mem::zeroed;
+#[cfg(unix)]
+use libc::{fchmod, mode_t, rename, stat, unlink};
+#[cfg(all(unix, feature = "std"))]
+use std::os::unix::ffi::OsStrExt;
+#[cfg(all(target_os = "windows", feature = "std"))]
+use std::os::windows::ffi::OsStrExt;
+
+/// Writes a file by replacing it atomically: readers see either the old contents
+/// or the complete new contents, never a partially written file.
+///
+/// The new contents are written to a temporary sibling file (`<path>.tmpXXXXXX`), which
+/// [`Self::commit`] flushes to disk and renames over the destination. If the writer is
+/// dropped without committing, the temporary file is removed.
+///
+/// On Unix, the rename uses `rename`, followed by an `fsync` of the parent directory.
+/// On Windows, it uses `MoveFileExW` with `MOVEFILE_REPLACE_EXISTING | MOVEFILE_WRITE_THROUGH`.
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "mmap")]
+/// # {
+/// use lightweight_mmap::{AtomicFileWriter, ReadWriteMmap};
+///
+/// let writer = AtomicFileWriter::create("index.bin", 4096).unwrap();
+/// {
+///     let mut mapping = ReadWriteMmap::new(writer.handle(), 0, 4096).unwrap();
+///     mapping.as_mut_slice()[..5].copy_from_slice(b"Hello");
+/// }
+/// writer.commit().unwrap();
+/// # }
+/// ```
+pub struct AtomicFileWriter {
+    handle: ReadWriteFileHandle,
+    #[cfg(unix)]
+    temp_path: CString,
+    #[cfg(unix)]
+    path: CString,
+    /// NUL-terminated UTF-16 paths.
+    #[cfg(target_os = "windows")]
+    temp_path: Vec<u16>,
+    #[cfg(target_os = "windows")]
+    path: Vec<u16>,
+    committed: bool,
+}
+
+impl AtomicFileWriter {
+    /// Starts writing a replacement for the file at `path`, with pre-allocated size.
+    ///
+    /// On Unix, if `path` already exists, the replacement keeps its permissions; otherwise
+    /// it is created with [`DEFAULT_FILE_MODE`]. On Windows, the replacement is created with
+    /// default attributes, and inherits the security of its directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file to replace (or create).
+    /// * `size` - The size to pre-allocate in bytes
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the temporary file cannot be created, its permissions
+    /// cannot be set, or pre-allocation fails.
+    #[cfg(feature = "std")]
+    pub fn create<P: AsRef<std::path::Path>>(path: P, size: i64) -> Result<Self, HandleOpenError> {
+        #[cfg(unix)]
+        {
+            Self::create_bytes(path.as_ref().as_os_str().as_bytes(), size)
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            let wide: Vec<u16> = path.as_ref().as_os_str().encode_wide().collect();
+            Self::create_wide(&wide, size)
+        }
+    }
+
+    /// Starts writing a replacement for the file at `path`, with pre-allocated size.
+    ///
+    /// On Unix, if `path` already exists, the replacement keeps its permissions; otherwise
+    /// it is created with [`DEFAULT_FILE_MODE`]. On Windows, the replacement is created with
+    /// default attributes, and inherits the security of its directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file to replace (or create).
+    /// * `size` - The size to pre-allocate in bytes
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the temporary file cannot be created, its permissions
+    /// cannot be set, or pre-allocation fails.
+    #[cfg(not(feature = "std"))]
+    pub fn create(path: &str, size: i64) -> Result<Self, HandleOpenError> {
+        #[cfg(unix)]
+        {
+            Self::create_bytes(path.as_bytes(), size)
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            let wide = crate::util::to_wide(path)
+                .map_err(|code| HandleOpenError::failed_to_convert_path(code, path))?;
+            Self::create_wide(&wide[..wide.len() - 1], size)
+        }
+    }
+
+    #[cfg(unix)]
+    fn create_bytes(path: &[u8], size: i64) -> Result<Self, HandleOpenError> {
+        let c_path = CString::new(path)
+            .map_err(|_| HandleOpenError::failed_to_open_file_handle_unix_bytes(-1, path))?;
+        let (fd, temp_path) = unix_common::create_unique(path, b".tmp")?;
+        let writer = AtomicFileWriter {
+            handle: unsafe { ReadWriteFileHandle::from_fd(fd) },
+            temp_path,
+            path: c_path,
+            committed: false,
+        };
+
+        // mkstemp creates the file as 0600, which should not leak onto the destination.
+        let mut st: stat = unsafe { zeroed() };
+        let mode = match unsafe { libc::stat(writer.path.as_ptr(), &mut st) } {
+            0 => (st.st_mode & 0o7777) as mode_t,
+            _ => DEFAULT_FILE_MODE as mode_t,
+        };
+        if unsafe { fchmod(fd, mode) } != 0 {
+            return Err(HandleOpenError::failed_to_open_file_handle_unix_bytes(
+                errno::errno().0,
+                path,
+            ));
+        }
+
+        unix_common::set_file_size(fd, size)?;
+        Ok(writer)
+    }
+
+    /// Creates the writer for `path`, given as UTF-16 without a NUL terminator.
+    #[cfg(target_os = "windows")]
+    fn create_wide(path: &[u16], size: i64) -> Result<Self, HandleOpenError> {
+        let (handle, temp_path) = windows_common::create_unique(path, ".tmp")?;
+        let mut c_path = Vec::with_capacity(path.len() + 1);
+        c_path.extend_from_slice(path);
+        c_path.push(0);
+
+        let writer = AtomicFileWriter {
+            handle: unsafe { ReadWriteFileHandle::from_handle(handle) },
+            temp_path,
+            path: c_path,
+            committed: false,
+        };
+
+        windows_common::set_file_size(handle, size)?;
+        Ok(writer)
+    }
+
+    /// Returns the handle to the temporary file, to write the new contents through.
+    ///
+    /// This can also be used to create a `ReadWriteMmap` over the new contents.
+    /// Any such mapping must be dropped before calling [`Self::commit`].
+    pub fn handle(&self) -> &ReadWriteFileHandle {
+        &self.handle
+    }
+
+    /// Replaces the destination with the new contents.
+    ///
+    /// The temporary file is flushed to disk, renamed over the destination, and then the
+    /// rename itself is made durable (by flushing the parent directory on Unix, and with
+    /// `MOVEFILE_WRITE_THROUGH` on Windows).
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleIoError`] if any step fails. If the rename did not happen,
+    /// the destination is left untouched and the temporary file is removed.
+    pub fn commit(mut self) -> Result<(), HandleIoError> {
+        #[cfg(unix)]
+        {
+            unix_common::sync_all(self.handle.handle().fd())?;
+
+            if unsafe { rename(self.temp_path.as_ptr(), self.path.as_ptr()) } != 0 {
+                return Err(HandleIoError::FailedToRename(errno::errno().0));
+            }
+
+            self.committed = true;
+            unix_common::sync_parent_dir(self.path.as_bytes())
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            windows_common::sync_all(self.handle.handle().handle())?;
+            windows_common::replace_file(&self.temp_path, &self.path)?;
+            self.committed = true;
+            Ok(())
+        }
+    }
+}
+
+impl Drop for AtomicFileWriter {
+    fn drop(&mut self) {
+        if !self.committed {
+            #[cfg(unix)]
+            unsafe {
+                unlink(self.temp_path.as_ptr());
+            }
+
+            #[cfg(target_os = "windows")]
+            windows_common::delete_file(&self.temp_path);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::fs::{read, read_dir, write};
+    use tempfile::TempDir;
+
+    #[test]
+    fn commit_replaces_destination() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("index.bin");
+        write(&path, b"Old contents").unwrap();
+
+        let writer = AtomicFileWriter::create(&path, 5).unwrap();
+        writer.handle().write_all_at(0, b"Hello").unwrap();
+        assert_eq!(read(&path).unwrap(), b"Old contents");
+
+        writer.commit().unwrap();
+        assert_eq!(read(&path).unwrap(), b"Hello");
+        assert_eq!(read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn drop_without_commit_removes_temp_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("index.bin");
+
+        let writer = AtomicFileWriter::create(&path, 16).unwrap();
+        writer.handle().write_all_at(0, b"Hello").unwrap();
+        assert_eq!(read_dir(dir.path()).unwrap().count(), 1);
+
+        drop(writer);
+        assert_eq!(read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn commit_keeps_destination_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("index.bin");
+        write(&path, b"Old contents").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o640)).unwrap();
+
+        AtomicFileWriter::create(&path, 0)
+            .unwrap()
+            .commit()
+            .unwrap();
+
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+    }
+}
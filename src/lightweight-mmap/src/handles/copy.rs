@@ -0,0 +1,284 @@
+use super::*;
+
+/// Size of the source windows mapped at once by the [`CopyStrategy::Mmap`] strategy.
+#[cfg(feature = "mmap")]
+const MMAP_WINDOW: u64 = 16 * 1024 * 1024;
+
+/// Size of the buffer used by the [`CopyStrategy::Buffered`] strategy.
+const BUFFER_SIZE: usize = 64 * 1024;
+
+/// How [`copy_range`] copied the data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyStrategy {
+    /// The range was shared with `FICLONERANGE` (reflink) on a copy-on-write filesystem
+    /// such as Btrfs or XFS. No data was copied, and no extra disk space is used.
+    Reflink,
+    /// The kernel copied the data with `copy_file_range`, without passing it through user space.
+    CopyFileRange,
+    /// The source was memory mapped window by window, and written into the destination.
+    Mmap,
+    /// The data was read into, and written out from, an intermediate buffer.
+    Buffered,
+}
+
+/// The result of a successful [`copy_range`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CopyOutcome {
+    /// How the data was copied.
+    pub strategy: CopyStrategy,
+    /// The number of bytes copied. Less than requested only if the end of the source was reached.
+    pub copied: u64,
+}
+
+/// Copies `len` bytes from `src` at `src_offset` into `dst` at `dst_offset`, using the
+/// cheapest method available.
+///
+/// On Linux and Android, a reflink (`FICLONERANGE`) is tried first, which is instant on
+/// copy-on-write filesystems, followed by `copy_file_range`. Otherwise, or where these are not
+/// possible for the given files and offsets, the data is copied through memory mappings of the
+/// source (with the `mmap` feature), or through a buffer. The returned [`CopyOutcome`] says
+/// which of these was used.
+///
+/// The destination grows as needed. The range is clamped to the end of the source.
+/// `src` and `dst` may be the same file, with overlapping ranges; these are copied through a
+/// buffer, in the order that reads every byte before it is overwritten.
+///
+/// # Arguments
+///
+/// * `src` - The file to copy from.
+/// * `src_offset` - The offset into `src` to start copying from.
+/// * `dst` - The file to copy into.
+/// * `dst_offset` - The offset into `dst` to start copying to.
+/// * `len` - The number of bytes to copy.
+///
+/// # Errors
+///
+/// Returns a [`HandleIoError`] if the copy fails. Part of the range may have been copied.
+pub fn copy_range(
+    src: &ReadOnlyFileHandle,
+    src_offset: u64,
+    dst: &ReadWriteFileHandle,
+    dst_offset: u64,
+    len: u64,
+) -> Result<CopyOutcome, HandleIoError> {
+    let len = len.min(src.metadata()?.size.saturating_sub(src_offset));
+
+    if len > 0
+        && src_offset < dst_offset.saturating_add(len)
+        && dst_offset < src_offset + len
+        && src.metadata()?.same_file(&dst.metadata()?)
+    {
+        let copied = if dst_offset > src_offset {
+            copy_buffered_backwards(src, src_offset, dst, dst_offset, len)?
+        } else {
+            copy_buffered(src, src_offset, dst, dst_offset, len)?
+        };
+        return Ok(CopyOutcome {
+            strategy: CopyStrategy::Buffered,
+            copied,
+        });
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let (src_fd, dst_fd) = (src.handle().fd(), dst.handle().fd());
+
+        if len > 0 && unix_common::clone_range(src_fd, src_offset, dst_fd, dst_offset, len) {
+            return Ok(CopyOutcome {
+                strategy: CopyStrategy::Reflink,
+                copied: len,
+            });
+        }
+
+        match unix_common::copy_file_range(src_fd, src_offset, dst_fd, dst_offset, len) {
+            Ok(copied) => {
+                return Ok(CopyOutcome {
+                    strategy: CopyStrategy::CopyFileRange,
+                    copied,
+                })
+            }
+            Err(HandleIoError::Unsupported) => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    #[cfg(feature = "mmap")]
+    if let Some(copied) = copy_mapped(src, src_offset, dst, dst_offset, len)? {
+        return Ok(CopyOutcome {
+            strategy: CopyStrategy::Mmap,
+            copied,
+        });
+    }
+
+    Ok(CopyOutcome {
+        strategy: CopyStrategy::Buffered,
+        copied: copy_buffered(src, src_offset, dst, dst_offset, len)?,
+    })
+}
+
+/// Copies through memory mappings of the source, one window at a time.
+///
+/// Returns `None` if the source cannot be mapped at all. If a later window cannot be
+/// mapped, that window is copied through a buffer instead.
+#[cfg(feature = "mmap")]
+fn copy_mapped(
+    src: &ReadOnlyFileHandle,
+    src_offset: u64,
+    dst: &ReadWriteFileHandle,
+    dst_offset: u64,
+    len: u64,
+) -> Result<Option<u64>, HandleIoError> {
+    use crate::mmap::{MemoryAdvice, ReadOnlyMmap};
+
+    let mut copied = 0;
+    while copied < len {
+        let window = (len - copied).min(MMAP_WINDOW);
+        match ReadOnlyMmap::new(src, src_offset + copied, window as usize) {
+            Ok(mapping) => {
+                mapping.advise(MemoryAdvice::SEQUENTIAL);
+                dst.write_all_at(dst_offset + copied, mapping.as_slice())?;
+            }
+            Err(_) if copied == 0 => return Ok(None),
+            Err(_) => {
+                copy_buffered(src, src_offset + copied, dst, dst_offset + copied, window)?;
+            }
+        }
+
+        copied += window;
+    }
+
+    Ok(Some(copied))
+}
+
+/// Copies by reading into, and writing out from, a buffer.
+fn copy_buffered(
+    src: &ReadOnlyFileHandle,
+    src_offset: u64,
+    dst: &ReadWriteFileHandle,
+    dst_offset: u64,
+    len: u64,
+) -> Result<u64, HandleIoError> {
+    let mut buffer = [0u8; BUFFER_SIZE];
+    let mut copied = 0;
+
+    while copied < len {
+        let chunk = (len - copied).min(BUFFER_SIZE as u64) as usize;
+        let read = src.read_at(src_offset + copied, &mut buffer[..chunk])?;
+        if read == 0 {
+            break;
+        }
+
+        dst.write_all_at(dst_offset + copied, &buffer[..read])?;
+        copied += read as u64;
+    }
+
+    Ok(copied)
+}
+
+/// Copies through a buffer from the end of the range to the start, so that a destination
+/// overlapping the end of the source in the same file does not overwrite unread data.
+fn copy_buffered_backwards(
+    src: &ReadOnlyFileHandle,
+    src_offset: u64,
+    dst: &ReadWriteFileHandle,
+    dst_offset: u64,
+    len: u64,
+) -> Result<u64, HandleIoError> {
+    let mut buffer = [0u8; BUFFER_SIZE];
+    let mut remaining = len;
+
+    while remaining > 0 {
+        let chunk = remaining.min(BUFFER_SIZE as u64) as usize;
+        remaining -= chunk as u64;
+        src.read_exact_at(src_offset + remaining, &mut buffer[..chunk])?;
+        dst.write_all_at(dst_offset + remaining, &buffer[..chunk])?;
+    }
+
+    Ok(len)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+    use tempfile::NamedTempFile;
+
+    fn source_file(len: usize) -> (NamedTempFile, Vec<u8>) {
+        let file = NamedTempFile::new().unwrap();
+        let data: Vec<u8> = (0..len).map(|x| (x % 251) as u8).collect();
+        std::fs::write(file.path(), &data).unwrap();
+        (file, data)
+    }
+
+    #[test]
+    fn copies_range_into_destination() {
+        let (src_file, data) = source_file(256 * 1024);
+        let dst_file = NamedTempFile::new().unwrap();
+        let src = ReadOnlyFileHandle::open(src_file.path()).unwrap();
+        let dst = ReadWriteFileHandle::open(dst_file.path()).unwrap();
+
+        let outcome = copy_range(&src, 4096, &dst, 8192, 128 * 1024).unwrap();
+        assert_eq!(outcome.copied, 128 * 1024);
+
+        let copied = std::fs::read(dst_file.path()).unwrap();
+        assert_eq!(copied.len(), 8192 + 128 * 1024);
+        assert_eq!(&copied[8192..], &data[4096..4096 + 128 * 1024]);
+    }
+
+    #[test]
+    fn copy_is_clamped_to_end_of_source() {
+        let (src_file, data) = source_file(10_000);
+        let dst_file = NamedTempFile::new().unwrap();
+        let src = ReadOnlyFileHandle::open(src_file.path()).unwrap();
+        let dst = ReadWriteFileHandle::open(dst_file.path()).unwrap();
+
+        let outcome = copy_range(&src, 9_000, &dst, 0, 1 << 20).unwrap();
+        assert_eq!(outcome.copied, 1_000);
+        assert_eq!(std::fs::read(dst_file.path()).unwrap(), &data[9_000..]);
+
+        let outcome = copy_range(&src, 20_000, &dst, 0, 100).unwrap();
+        assert_eq!(outcome.copied, 0);
+    }
+
+    #[test]
+    fn buffered_copy_matches_source() {
+        let (src_file, data) = source_file(BUFFER_SIZE * 2 + 123);
+        let dst_file = NamedTempFile::new().unwrap();
+        let src = ReadOnlyFileHandle::open(src_file.path()).unwrap();
+        let dst = ReadWriteFileHandle::open(dst_file.path()).unwrap();
+
+        let copied = copy_buffered(&src, 0, &dst, 0, data.len() as u64).unwrap();
+        assert_eq!(copied, data.len() as u64);
+        assert_eq!(std::fs::read(dst_file.path()).unwrap(), data);
+    }
+
+    #[test]
+    #[cfg(feature = "mmap")]
+    fn mapped_copy_matches_source() {
+        let (src_file, data) = source_file(100_000);
+        let dst_file = NamedTempFile::new().unwrap();
+        let src = ReadOnlyFileHandle::open(src_file.path()).unwrap();
+        let dst = ReadWriteFileHandle::open(dst_file.path()).unwrap();
+
+        let copied = copy_mapped(&src, 1, &dst, 0, 99_999).unwrap();
+        assert_eq!(copied, Some(99_999));
+        assert_eq!(std::fs::read(dst_file.path()).unwrap(), &data[1..]);
+    }
+
+    #[test]
+    fn overlapping_copy_within_file_matches_memmove() {
+        let (file, mut data) = source_file(BUFFER_SIZE * 3);
+        let src = ReadOnlyFileHandle::open(file.path()).unwrap();
+        let dst = ReadWriteFileHandle::open(file.path()).unwrap();
+
+        let outcome = copy_range(&src, 100, &dst, 5_000, BUFFER_SIZE as u64 * 2).unwrap();
+        assert_eq!(outcome.copied, BUFFER_SIZE as u64 * 2);
+        data.copy_within(100..100 + BUFFER_SIZE * 2, 5_000);
+        assert_eq!(std::fs::read(file.path()).unwrap(), data);
+
+        let outcome = copy_range(&src, 5_000, &dst, 10, BUFFER_SIZE as u64 * 2).unwrap();
+        assert_eq!(outcome.copied, BUFFER_SIZE as u64 * 2);
+        data.copy_within(5_000..5_000 + BUFFER_SIZE * 2, 10);
+        assert_eq!(std::fs::read(file.path()).unwrap(), data);
+    }
+}
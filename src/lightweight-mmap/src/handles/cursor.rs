@@ -0,0 +1,278 @@
+use super::*;
+use std::io::{self, IoSlice, IoSliceMut, Read, Seek, SeekFrom, Write};
+
+impl From<HandleIoError> for io::Error {
+    #[allow(clippy::unnecessary_cast)] // Error codes are u32 on Windows
+    fn from(error: HandleIoError) -> Self {
+        match error {
+            HandleIoError::FailedToRead(code)
+            | HandleIoError::FailedToWrite(code)
+            | HandleIoError::FailedToSync(code)
+            | HandleIoError::FailedToGetMetadata(code)
+            | HandleIoError::FailedToLock(code)
+            | HandleIoError::FailedToRename(code) => io::Error::from_raw_os_error(code as i32),
+            #[cfg(unix)]
+            HandleIoError::FailedToPersist(code)
+            | HandleIoError::FailedToAllocate(code)
+            | HandleIoError::FailedToQueryExtents(code)
+            | HandleIoError::FailedToCopy(code)
+            | HandleIoError::FailedToAdvise(code) => io::Error::from_raw_os_error(code),
+            HandleIoError::Unsupported => io::Error::new(io::ErrorKind::Unsupported, error),
+            HandleIoError::WouldBlock => io::Error::new(io::ErrorKind::WouldBlock, error),
+            HandleIoError::UnexpectedEof => io::Error::new(io::ErrorKind::UnexpectedEof, error),
+            HandleIoError::WriteZero => io::Error::new(io::ErrorKind::WriteZero, error),
+        }
+    }
+}
+
+/// Computes the new position of a cursor after a seek.
+fn seek_position(
+    position: u64,
+    seek: SeekFrom,
+    size: impl FnOnce() -> Result<i64, HandleOpenError>,
+) -> io::Result<u64> {
+    let (base, offset) = match seek {
+        SeekFrom::Start(offset) => return Ok(offset),
+        SeekFrom::Current(offset) => (position, offset),
+        SeekFrom::End(offset) => (size().map_err(io::Error::other)? as u64, offset),
+    };
+
+    base.checked_add_signed(offset).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "invalid seek to a negative or overflowing position",
+        )
+    })
+}
+
+/// A cursor over a [`ReadOnlyFileHandle`] which implements [`Read`] and [`Seek`].
+///
+/// The cursor tracks its own position and reads with positional I/O, so it does
+/// not interfere with other users of the same handle.
+pub struct ReadOnlyFileCursor {
+    handle: ReadOnlyFileHandle,
+    position: u64,
+}
+
+impl ReadOnlyFileCursor {
+    /// Creates a new cursor at the start of the file.
+    pub fn new(handle: ReadOnlyFileHandle) -> Self {
+        ReadOnlyFileCursor {
+            handle,
+            position: 0,
+        }
+    }
+
+    /// Returns the current position of the cursor.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Sets the position of the cursor.
+    pub fn set_position(&mut self, position: u64) {
+        self.position = position;
+    }
+
+    /// Returns a reference to the underlying file handle.
+    pub fn get_ref(&self) -> &ReadOnlyFileHandle {
+        &self.handle
+    }
+
+    /// Consumes the cursor, returning the underlying file handle.
+    pub fn into_inner(self) -> ReadOnlyFileHandle {
+        self.handle
+    }
+}
+
+impl Read for ReadOnlyFileCursor {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.handle.read_at(self.position, buf)?;
+        self.position += read as u64;
+        Ok(read)
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        let read = self.handle.read_vectored_at(self.position, bufs)?.total();
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for ReadOnlyFileCursor {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.position = seek_position(self.position, pos, || self.handle.size())?;
+        Ok(self.position)
+    }
+
+    fn stream_position(&mut self) -> io::Result<u64> {
+        Ok(self.position)
+    }
+}
+
+/// A cursor over a [`ReadWriteFileHandle`] which implements [`Read`], [`Write`] and [`Seek`].
+///
+/// The cursor tracks its own position and reads/writes with positional I/O, so it does
+/// not interfere with other users of the same handle.
+pub struct ReadWriteFileCursor {
+    handle: ReadWriteFileHandle,
+    position: u64,
+}
+
+impl ReadWriteFileCursor {
+    /// Creates a new cursor at the start of the file.
+    pub fn new(handle: ReadWriteFileHandle) -> Self {
+        ReadWriteFileCursor {
+            handle,
+            position: 0,
+        }
+    }
+
+    /// Returns the current position of the cursor.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Sets the position of the cursor.
+    pub fn set_position(&mut self, position: u64) {
+        self.position = position;
+    }
+
+    /// Returns a reference to the underlying file handle.
+    pub fn get_ref(&self) -> &ReadWriteFileHandle {
+        &self.handle
+    }
+
+    /// Consumes the cursor, returning the underlying file handle.
+    pub fn into_inner(self) -> ReadWriteFileHandle {
+        self.handle
+    }
+}
+
+impl Read for ReadWriteFileCursor {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.handle.read_at(self.position, buf)?;
+        self.position += read as u64;
+        Ok(read)
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        let read = self.handle.read_vectored_at(self.position, bufs)?.total();
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl Write for ReadWriteFileCursor {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.handle.write_at(self.position, buf)?;
+        self.position += written as u64;
+        Ok(written)
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        let written = self.handle.write_vectored_at(self.position, bufs)?.total();
+        self.position += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        // Writes go straight to the OS, there is nothing buffered here.
+        Ok(())
+    }
+}
+
+impl Seek for ReadWriteFileCursor {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.position = seek_position(self.position, pos, || self.handle.size())?;
+        Ok(self.position)
+    }
+
+    fn stream_position(&mut self) -> io::Result<u64> {
+        Ok(self.position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{string::String, vec::Vec};
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn can_read_to_end() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"Hello, World!").unwrap();
+        file.flush().unwrap();
+
+        let handle = ReadOnlyFileHandle::open(file.path()).unwrap();
+        let mut cursor = ReadOnlyFileCursor::new(handle);
+
+        let mut content = String::new();
+        cursor.read_to_string(&mut content).unwrap();
+        assert_eq!(content, "Hello, World!");
+        assert_eq!(cursor.position(), 13);
+    }
+
+    #[test]
+    fn can_seek_and_read() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"Hello, World!").unwrap();
+        file.flush().unwrap();
+
+        let handle = ReadOnlyFileHandle::open(file.path()).unwrap();
+        let mut cursor = ReadOnlyFileCursor::new(handle);
+
+        let mut buf = [0u8; 5];
+        assert_eq!(cursor.seek(SeekFrom::End(-6)).unwrap(), 7);
+        cursor.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"World");
+
+        assert_eq!(cursor.seek(SeekFrom::Current(-12)).unwrap(), 0);
+        cursor.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"Hello");
+
+        assert!(cursor.seek(SeekFrom::Current(-6)).is_err());
+        assert_eq!(cursor.position(), 5);
+    }
+
+    #[test]
+    fn read_exact_past_end_fails() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"Hello").unwrap();
+        file.flush().unwrap();
+
+        let handle = ReadOnlyFileHandle::open(file.path()).unwrap();
+        let mut cursor = ReadOnlyFileCursor::new(handle);
+
+        let mut buf = [0u8; 8];
+        let error = cursor.read_exact(&mut buf).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn can_write_seek_and_read_back() {
+        let file = NamedTempFile::new().unwrap();
+        let handle = ReadWriteFileHandle::open(file.path()).unwrap();
+        let mut cursor = ReadWriteFileCursor::new(handle);
+
+        cursor.write_all(b"Hello, World!").unwrap();
+        cursor.seek(SeekFrom::Start(7)).unwrap();
+        cursor.write_all(b"WORLD").unwrap();
+        assert_eq!(cursor.position(), 12);
+
+        cursor.rewind().unwrap();
+        let mut content = Vec::new();
+        cursor.read_to_end(&mut content).unwrap();
+        assert_eq!(content, b"Hello, WORLD!");
+        assert_eq!(cursor.into_inner().size().unwrap(), 13);
+    }
+
+    #[test]
+    fn io_error_conversion_preserves_kind() {
+        let error: io::Error = HandleIoError::UnexpectedEof.into();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+
+        let error: io::Error = HandleIoError::WriteZero.into();
+        assert_eq!(error.kind(), io::ErrorKind::WriteZero);
+    }
+}
@@ -0,0 +1,212 @@
+use super::*;
+use core::ffi::CStr;
+use libc::{c_int, close, O_CLOEXEC, O_DIRECTORY, O_RDONLY};
+#[cfg(feature = "std")]
+use std::os::unix::ffi::OsStrExt;
+use unix_common::{open_cstr_with_flags, open_with_flags};
+
+/// A handle to an open directory, used as the base for directory-relative opens
+/// such as [`ReadOnlyFileHandle::open_at`] and [`ReadWriteFileHandle::create_preallocated_at`].
+///
+/// Files opened relative to a [`DirHandle`] are resolved from the open directory itself,
+/// so the directory's path is not walked again for every file, and swapping out the
+/// directory (e.g. renaming it, or replacing it with a symlink) after it was opened
+/// does not redirect later opens.
+///
+/// **Note:** Unix only.
+pub struct DirHandle {
+    fd: c_int,
+}
+
+unsafe impl Send for DirHandle {}
+unsafe impl Sync for DirHandle {}
+
+impl DirHandle {
+    /// Opens a directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the directory to open.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the directory cannot be opened, or is not a directory.
+    #[cfg(feature = "std")]
+    pub fn open<P: AsRef<std::path::Path>>(path: P) -> Result<Self, HandleOpenError> {
+        let fd = open_with_flags(
+            path.as_ref().as_os_str().as_bytes(),
+            O_RDONLY | O_DIRECTORY | O_CLOEXEC,
+            DEFAULT_FILE_MODE,
+        )?;
+        Ok(DirHandle { fd })
+    }
+
+    /// Opens a directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the directory to open.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the directory cannot be opened, or is not a directory.
+    #[cfg(not(feature = "std"))]
+    pub fn open(path: &str) -> Result<Self, HandleOpenError> {
+        let fd = open_with_flags(
+            path.as_bytes(),
+            O_RDONLY | O_DIRECTORY | O_CLOEXEC,
+            DEFAULT_FILE_MODE,
+        )?;
+        Ok(DirHandle { fd })
+    }
+
+    /// Opens a directory from a NUL-terminated path, without allocating.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the directory to open.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the directory cannot be opened, or is not a directory.
+    pub fn open_cstr(path: &CStr) -> Result<Self, HandleOpenError> {
+        let fd = open_cstr_with_flags(path, O_RDONLY | O_DIRECTORY | O_CLOEXEC, DEFAULT_FILE_MODE)?;
+        Ok(DirHandle { fd })
+    }
+
+    /// Returns the raw file descriptor of the directory.
+    pub fn fd(&self) -> c_int {
+        self.fd
+    }
+}
+
+impl Drop for DirHandle {
+    fn drop(&mut self) {
+        unsafe {
+            close(self.fd);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+mod std_impls {
+    use super::*;
+    use std::os::fd::{AsFd, AsRawFd, BorrowedFd, RawFd};
+
+    impl AsRawFd for DirHandle {
+        fn as_raw_fd(&self) -> RawFd {
+            self.fd
+        }
+    }
+
+    impl AsFd for DirHandle {
+        fn as_fd(&self) -> BorrowedFd<'_> {
+            unsafe { BorrowedFd::borrow_raw(self.fd) }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::fs::{create_dir, rename, write};
+    use tempfile::{NamedTempFile, TempDir};
+
+    #[test]
+    fn open_fails_on_regular_file() {
+        let file = NamedTempFile::new().unwrap();
+        assert!(DirHandle::open(file.path()).is_err());
+    }
+
+    #[test]
+    fn open_at_resolves_from_original_directory() {
+        let root = TempDir::new().unwrap();
+        let original = root.path().join("original");
+        create_dir(&original).unwrap();
+        write(original.join("data.bin"), b"Hello").unwrap();
+
+        let dir = DirHandle::open(&original).unwrap();
+
+        // Swap the directory out from under the handle.
+        rename(&original, root.path().join("moved")).unwrap();
+        create_dir(&original).unwrap();
+
+        let handle = ReadOnlyFileHandle::open_at(&dir, "data.bin").unwrap();
+        assert_eq!(handle.size().unwrap(), 5);
+    }
+
+    #[test]
+    fn can_create_and_open_relative_to_directory() {
+        let root = TempDir::new().unwrap();
+        let dir = DirHandle::open(root.path()).unwrap();
+
+        let handle = ReadWriteFileHandle::create_preallocated_at(&dir, "created.bin", 16).unwrap();
+        handle.write_all_at(0, b"Hello").unwrap();
+        drop(handle);
+        assert!(root.path().join("created.bin").exists());
+
+        let handle = FileHandleOptions::new()
+            .truncate(true)
+            .open_read_write_at(&dir, "created.bin")
+            .unwrap();
+        assert_eq!(handle.size().unwrap(), 0);
+        assert!(ReadOnlyFileHandle::open_at(&dir, "missing.bin").is_err());
+    }
+
+    #[test]
+    fn open_beneath_rejects_escapes() {
+        let root = TempDir::new().unwrap();
+        let outside = NamedTempFile::new().unwrap();
+        create_dir(root.path().join("sub")).unwrap();
+        std::os::unix::fs::symlink(outside.path(), root.path().join("link.bin")).unwrap();
+        std::os::unix::fs::symlink("/tmp", root.path().join("sub/escape")).unwrap();
+
+        let dir = DirHandle::open(root.path()).unwrap();
+        for path in [
+            "../x.bin",
+            "sub/../../x.bin",
+            "/etc/passwd",
+            "link.bin",
+            "sub/escape/x.bin",
+        ] {
+            let result = ReadWriteFileHandle::create_preallocated_beneath(&dir, path, 0);
+            assert!(
+                matches!(result, Err(HandleOpenError::PathNotBeneathRoot(..))),
+                "{path}"
+            );
+        }
+    }
+
+    #[test]
+    fn open_beneath_allows_nested_paths() {
+        let root = TempDir::new().unwrap();
+        create_dir(root.path().join("sub")).unwrap();
+
+        let dir = DirHandle::open(root.path()).unwrap();
+        ReadWriteFileHandle::create_preallocated_beneath(&dir, "./sub//nested.bin", 8).unwrap();
+
+        let handle = ReadOnlyFileHandle::open_beneath(&dir, "sub/nested.bin").unwrap();
+        assert_eq!(handle.size().unwrap(), 8);
+    }
+
+    #[test]
+    fn walk_fallback_does_not_follow_symlinks() {
+        use libc::{close, O_RDONLY};
+
+        let root = TempDir::new().unwrap();
+        create_dir(root.path().join("sub")).unwrap();
+        write(root.path().join("sub/data.bin"), b"Hello").unwrap();
+        std::os::unix::fs::symlink(root.path().join("sub"), root.path().join("link")).unwrap();
+
+        let dir = DirHandle::open(root.path()).unwrap();
+        let fd = unix_common::openat_beneath_walk(dir.fd(), b"sub/data.bin", O_RDONLY, 0).unwrap();
+        assert_eq!(unix_common::get_file_size(fd).unwrap(), 5);
+        unsafe { close(fd) };
+
+        let result = unix_common::openat_beneath_walk(dir.fd(), b"link/data.bin", O_RDONLY, 0);
+        assert!(matches!(
+            result,
+            Err(HandleOpenError::PathNotBeneathRoot(..))
+        ));
+    }
+}
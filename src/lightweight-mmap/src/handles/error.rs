@@ -46,6 +46,26 @@ pub enum HandleOpenError {
     )]
     FailedToSetFileSize(#[cfg(target_os = "windows")] u32, #[cfg(unix)] i32),
 
+    /// Failed to duplicate the file handle.
+    #[cfg_attr(
+        not(feature = "no-format"),
+        error("Failed to duplicate file handle. Error code: {0}")
+    )]
+    FailedToDuplicateHandle(#[cfg(target_os = "windows")] u32, #[cfg(unix)] i32),
+
+    /// The path would resolve outside of the root directory it was opened beneath,
+    /// for example through `..`, an absolute path or a symbolic link (Unix only).
+    #[cfg(unix)]
+    #[cfg_attr(
+        all(not(feature = "no-format"), debug_assertions),
+        error("Path is not beneath the root directory. Path: {0}")
+    )]
+    #[cfg_attr(
+        all(not(feature = "no-format"), not(debug_assertions)),
+        error("Path is not beneath the root directory")
+    )]
+    PathNotBeneathRoot(#[cfg(debug_assertions)] String),
+
     /// Failed to create file mapping.
     #[cfg(all(target_os = "windows", feature = "mmap"))]
     #[cfg_attr(
@@ -59,6 +79,109 @@ pub enum HandleOpenError {
     FailedToCreateFileMapping(u32, #[cfg(debug_assertions)] String),
 }
 
+/// Represents errors that can occur when reading from or writing to a file handle.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(not(feature = "no-format"), derive(thiserror::Error))]
+pub enum HandleIoError {
+    /// Failed to read from the file.
+    #[cfg_attr(
+        not(feature = "no-format"),
+        error("Failed to read from file. Error code: {0}")
+    )]
+    FailedToRead(#[cfg(target_os = "windows")] u32, #[cfg(unix)] i32),
+
+    /// Failed to write to the file.
+    #[cfg_attr(
+        not(feature = "no-format"),
+        error("Failed to write to file. Error code: {0}")
+    )]
+    FailedToWrite(#[cfg(target_os = "windows")] u32, #[cfg(unix)] i32),
+
+    /// Failed to flush the file to the storage device.
+    #[cfg_attr(
+        not(feature = "no-format"),
+        error("Failed to sync file. Error code: {0}")
+    )]
+    FailedToSync(#[cfg(target_os = "windows")] u32, #[cfg(unix)] i32),
+
+    /// Failed to query the metadata of the file.
+    #[cfg_attr(
+        not(feature = "no-format"),
+        error("Failed to get file metadata. Error code: {0}")
+    )]
+    FailedToGetMetadata(#[cfg(target_os = "windows")] u32, #[cfg(unix)] i32),
+
+    /// Failed to lock or unlock the file.
+    #[cfg_attr(
+        not(feature = "no-format"),
+        error("Failed to lock file. Error code: {0}")
+    )]
+    FailedToLock(#[cfg(target_os = "windows")] u32, #[cfg(unix)] i32),
+
+    /// Failed to rename a file over its destination.
+    #[cfg_attr(
+        not(feature = "no-format"),
+        error("Failed to rename file. Error code: {0}")
+    )]
+    FailedToRename(#[cfg(target_os = "windows")] u32, #[cfg(unix)] i32),
+
+    /// Failed to give a temporary file a name in the file system (Unix only).
+    #[cfg(unix)]
+    #[cfg_attr(
+        not(feature = "no-format"),
+        error("Failed to persist temporary file. Error code: {0}")
+    )]
+    FailedToPersist(i32),
+
+    /// Failed to allocate or deallocate space in the file (Unix only).
+    #[cfg(unix)]
+    #[cfg_attr(
+        not(feature = "no-format"),
+        error("Failed to allocate file space. Error code: {0}")
+    )]
+    FailedToAllocate(i32),
+
+    /// Failed to query the layout of the file (Unix only).
+    #[cfg(unix)]
+    #[cfg_attr(
+        not(feature = "no-format"),
+        error("Failed to query file extents. Error code: {0}")
+    )]
+    FailedToQueryExtents(i32),
+
+    /// Failed to copy data between files (Unix only).
+    #[cfg(unix)]
+    #[cfg_attr(
+        not(feature = "no-format"),
+        error("Failed to copy file range. Error code: {0}")
+    )]
+    FailedToCopy(i32),
+
+    /// Failed to give the operating system an access pattern hint (Unix only).
+    #[cfg(unix)]
+    #[cfg_attr(
+        not(feature = "no-format"),
+        error("Failed to advise file access. Error code: {0}")
+    )]
+    FailedToAdvise(i32),
+
+    /// The operation is not supported by this platform or filesystem.
+    #[cfg_attr(not(feature = "no-format"), error("Operation not supported"))]
+    Unsupported,
+
+    /// The lock could not be taken without blocking, as it is held elsewhere.
+    #[cfg_attr(not(feature = "no-format"), error("Lock is held elsewhere"))]
+    WouldBlock,
+
+    /// The end of the file was reached before the buffer could be filled.
+    #[cfg_attr(not(feature = "no-format"), error("Unexpected end of file"))]
+    UnexpectedEof,
+
+    /// The file accepted zero bytes before the whole buffer could be written.
+    #[cfg_attr(not(feature = "no-format"), error("Failed to write whole buffer"))]
+    WriteZero,
+}
+
 impl HandleOpenError {
     #[cfg(target_os = "windows")]
     #[allow(unused_variables)]
@@ -88,6 +211,22 @@ impl HandleOpenError {
         }
     }
 
+    /// Creates a [`HandleOpenError::FailedToOpenFileHandle`] for a path given as UTF-16.
+    /// Any unpaired surrogates in the path are replaced when stored for context.
+    #[cfg(target_os = "windows")]
+    #[allow(unused_variables)]
+    pub fn failed_to_open_file_handle_wide(err_code: u32, path: &[u16]) -> Self {
+        #[cfg(debug_assertions)]
+        {
+            Self::FailedToOpenFileHandle(err_code, String::from_utf16_lossy(path))
+        }
+
+        #[cfg(not(debug_assertions))]
+        {
+            Self::FailedToOpenFileHandle(err_code)
+        }
+    }
+
     #[cfg(all(target_os = "windows", feature = "mmap"))]
     #[allow(unused_variables)]
     pub fn failed_to_create_file_mapping(err_code: u32, path: &str) -> Self {
@@ -116,6 +255,37 @@ impl HandleOpenError {
         }
     }
 
+    /// Creates a [`HandleOpenError::FailedToOpenFileHandle`] for a path given as raw bytes.
+    /// Any non-UTF-8 sequences in the path are replaced when stored for context.
+    #[cfg(unix)]
+    #[allow(unused_variables)]
+    pub fn failed_to_open_file_handle_unix_bytes(err_code: i32, path: &[u8]) -> Self {
+        #[cfg(debug_assertions)]
+        {
+            Self::FailedToOpenFileHandle(err_code, String::from_utf8_lossy(path).into_owned())
+        }
+
+        #[cfg(not(debug_assertions))]
+        {
+            Self::FailedToOpenFileHandle(err_code)
+        }
+    }
+
+    /// Creates a [`HandleOpenError::PathNotBeneathRoot`] for a path given as raw bytes.
+    #[cfg(unix)]
+    #[allow(unused_variables)]
+    pub fn path_not_beneath_root(path: &[u8]) -> Self {
+        #[cfg(debug_assertions)]
+        {
+            Self::PathNotBeneathRoot(String::from_utf8_lossy(path).into_owned())
+        }
+
+        #[cfg(not(debug_assertions))]
+        {
+            Self::PathNotBeneathRoot()
+        }
+    }
+
     #[cfg(target_os = "windows")]
     pub fn failed_to_set_file_size(err_code: u32) -> Self {
         Self::FailedToSetFileSize(err_code)
@@ -185,6 +355,17 @@ impl Display for HandleOpenError {
                 f.write_str(&error_msg)
             }
 
+            #[cfg(all(unix, debug_assertions))]
+            Self::PathNotBeneathRoot(path) => {
+                let error_msg = unsafe {
+                    concat_2_no_overflow("Path is not beneath the root directory. Path: ", path)
+                };
+                f.write_str(&error_msg)
+            }
+
+            #[cfg(all(unix, not(debug_assertions)))]
+            Self::PathNotBeneathRoot() => f.write_str("Path is not beneath the root directory"),
+
             #[cfg(all(target_os = "windows", feature = "mmap", debug_assertions))]
             Self::FailedToCreateFileMapping(code, path) => {
                 let mut buffer = Buffer::new();
@@ -226,6 +407,141 @@ impl Display for HandleOpenError {
                 };
                 f.write_str(&error_msg)
             }
+
+            Self::FailedToDuplicateHandle(code) => {
+                let mut buffer = Buffer::new();
+                let code_str = buffer.format(*code);
+                let error_msg = unsafe {
+                    concat_2_no_overflow("Failed to duplicate file handle. Error code: ", code_str)
+                };
+                f.write_str(&error_msg)
+            }
+        }
+    }
+}
+
+#[cfg(all(feature = "no-format", feature = "std"))]
+impl std::error::Error for HandleOpenError {}
+
+#[cfg(all(feature = "no-format", feature = "std"))]
+impl std::error::Error for HandleIoError {}
+
+#[cfg(feature = "no-format")]
+impl Display for HandleIoError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        use itoa::*;
+        use nanokit::string_concat_unsafe::*;
+
+        match self {
+            Self::FailedToRead(code) => {
+                let mut buffer = Buffer::new();
+                let code_str = buffer.format(*code);
+                let error_msg = unsafe {
+                    concat_2_no_overflow("Failed to read from file. Error code: ", code_str)
+                };
+                f.write_str(&error_msg)
+            }
+
+            Self::FailedToWrite(code) => {
+                let mut buffer = Buffer::new();
+                let code_str = buffer.format(*code);
+                let error_msg = unsafe {
+                    concat_2_no_overflow("Failed to write to file. Error code: ", code_str)
+                };
+                f.write_str(&error_msg)
+            }
+
+            Self::FailedToSync(code) => {
+                let mut buffer = Buffer::new();
+                let code_str = buffer.format(*code);
+                let error_msg =
+                    unsafe { concat_2_no_overflow("Failed to sync file. Error code: ", code_str) };
+                f.write_str(&error_msg)
+            }
+
+            Self::FailedToGetMetadata(code) => {
+                let mut buffer = Buffer::new();
+                let code_str = buffer.format(*code);
+                let error_msg = unsafe {
+                    concat_2_no_overflow("Failed to get file metadata. Error code: ", code_str)
+                };
+                f.write_str(&error_msg)
+            }
+
+            Self::FailedToLock(code) => {
+                let mut buffer = Buffer::new();
+                let code_str = buffer.format(*code);
+                let error_msg =
+                    unsafe { concat_2_no_overflow("Failed to lock file. Error code: ", code_str) };
+                f.write_str(&error_msg)
+            }
+
+            Self::FailedToRename(code) => {
+                let mut buffer = Buffer::new();
+                let code_str = buffer.format(*code);
+                let error_msg = unsafe {
+                    concat_2_no_overflow("Failed to rename file. Error code: ", code_str)
+                };
+                f.write_str(&error_msg)
+            }
+
+            #[cfg(unix)]
+            Self::FailedToPersist(code) => {
+                let mut buffer = Buffer::new();
+                let code_str = buffer.format(*code);
+                let error_msg = unsafe {
+                    concat_2_no_overflow("Failed to persist temporary file. Error code: ", code_str)
+                };
+                f.write_str(&error_msg)
+            }
+
+            #[cfg(unix)]
+            Self::FailedToAllocate(code) => {
+                let mut buffer = Buffer::new();
+                let code_str = buffer.format(*code);
+                let error_msg = unsafe {
+                    concat_2_no_overflow("Failed to allocate file space. Error code: ", code_str)
+                };
+                f.write_str(&error_msg)
+            }
+
+            #[cfg(unix)]
+            Self::FailedToQueryExtents(code) => {
+                let mut buffer = Buffer::new();
+                let code_str = buffer.format(*code);
+                let error_msg = unsafe {
+                    concat_2_no_overflow("Failed to query file extents. Error code: ", code_str)
+                };
+                f.write_str(&error_msg)
+            }
+
+            #[cfg(unix)]
+            Self::FailedToCopy(code) => {
+                let mut buffer = Buffer::new();
+                let code_str = buffer.format(*code);
+                let error_msg = unsafe {
+                    concat_2_no_overflow("Failed to copy file range. Error code: ", code_str)
+                };
+                f.write_str(&error_msg)
+            }
+
+            #[cfg(unix)]
+            Self::FailedToAdvise(code) => {
+                let mut buffer = Buffer::new();
+                let code_str = buffer.format(*code);
+                let error_msg = unsafe {
+                    concat_2_no_overflow("Failed to advise file access. Error code: ", code_str)
+                };
+                f.write_str(&error_msg)
+            }
+
+            Self::Unsupported => f.write_str("Operation not supported"),
+
+            Self::WouldBlock => f.write_str("Lock is held elsewhere"),
+
+            Self::UnexpectedEof => f.write_str("Unexpected end of file"),
+
+            Self::WriteZero => f.write_str("Failed to write whole buffer"),
         }
     }
 }
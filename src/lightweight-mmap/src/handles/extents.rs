@@ -0,0 +1,222 @@
+use super::*;
+use core::marker::PhantomData;
+
+/// Whether an [`Extent`] of a file contains data, or is a hole.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtentKind {
+    /// The range contains data (which may still be zeros).
+    Data,
+    /// The range is a hole: it has no storage allocated, and reads back as zeros.
+    Hole,
+}
+
+/// A contiguous range of a file which is either all data, or all hole.
+///
+/// Returned by [`ReadOnlyFileHandle::extents`] and [`ReadWriteFileHandle::extents`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Extent {
+    /// The offset into the file where the extent starts.
+    pub offset: u64,
+    /// The length of the extent in bytes.
+    pub len: u64,
+    /// Whether the extent contains data or is a hole.
+    pub kind: ExtentKind,
+}
+
+/// An extent of a file as laid out on disk, as reported by `FIEMAP`.
+///
+/// Returned by [`ReadOnlyFileHandle::physical_extents`] and [`ReadWriteFileHandle::physical_extents`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PhysicalExtent {
+    /// The offset into the file where the extent starts.
+    pub logical: u64,
+    /// The offset on the underlying device where the extent starts.
+    pub physical: u64,
+    /// The length of the extent in bytes.
+    pub len: u64,
+    /// The raw `FIEMAP_EXTENT_*` flags of the extent, e.g. `FIEMAP_EXTENT_UNWRITTEN` (`0x800`)
+    /// for space which is allocated but reads back as zeros.
+    pub flags: u32,
+}
+
+/// An iterator over the data and hole [`Extent`]s of a file, in order.
+///
+/// The extents cover the whole file (as of when the iterator was created) without gaps.
+/// Uses `SEEK_DATA`/`SEEK_HOLE` where available. On platforms or filesystems without
+/// support, the whole file is reported as a single [`ExtentKind::Data`] extent.
+///
+/// Created by [`ReadOnlyFileHandle::extents`] and [`ReadWriteFileHandle::extents`].
+pub struct Extents<'a> {
+    #[cfg(unix)]
+    fd: libc::c_int,
+    position: u64,
+    size: u64,
+    _phantom: PhantomData<&'a ()>,
+}
+
+impl Extents<'_> {
+    #[cfg(unix)]
+    pub(crate) fn new(fd: libc::c_int, size: i64) -> Self {
+        Extents {
+            fd,
+            position: 0,
+            size: size.max(0) as u64,
+            _phantom: PhantomData,
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    pub(crate) fn new(size: i64) -> Self {
+        Extents {
+            position: 0,
+            size: size.max(0) as u64,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Finds the extent starting at the current position.
+    fn next_extent(&self) -> Result<Extent, HandleIoError> {
+        let (position, size) = (self.position, self.size);
+        let rest = Extent {
+            offset: position,
+            len: size - position,
+            kind: ExtentKind::Data,
+        };
+
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "android",
+            target_vendor = "apple",
+            target_os = "freebsd",
+            target_os = "dragonfly",
+            target_os = "solaris",
+            target_os = "illumos"
+        ))]
+        {
+            use libc::{SEEK_DATA, SEEK_HOLE};
+
+            let data = match unix_common::seek_extent(self.fd, position, SEEK_DATA) {
+                Ok(Some(data)) => data.min(size),
+                Ok(None) => size,
+                Err(HandleIoError::Unsupported) => return Ok(rest),
+                Err(e) => return Err(e),
+            };
+
+            if data > position {
+                return Ok(Extent {
+                    offset: position,
+                    len: data - position,
+                    kind: ExtentKind::Hole,
+                });
+            }
+
+            let hole = unix_common::seek_extent(self.fd, position, SEEK_HOLE)?
+                .unwrap_or(size)
+                .min(size);
+
+            // The file changed under us; report the rest as data rather than looping forever.
+            if hole <= position {
+                return Ok(rest);
+            }
+
+            Ok(Extent {
+                offset: position,
+                len: hole - position,
+                kind: ExtentKind::Data,
+            })
+        }
+
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_vendor = "apple",
+            target_os = "freebsd",
+            target_os = "dragonfly",
+            target_os = "solaris",
+            target_os = "illumos"
+        )))]
+        {
+            Ok(rest)
+        }
+    }
+}
+
+impl Iterator for Extents<'_> {
+    type Item = Result<Extent, HandleIoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position >= self.size {
+            return None;
+        }
+
+        match self.next_extent() {
+            Ok(extent) => {
+                self.position = extent.offset + extent.len;
+                Some(Ok(extent))
+            }
+            Err(e) => {
+                self.position = self.size;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn extents_cover_whole_file_in_order() {
+        let file = NamedTempFile::new().unwrap();
+        let handle = ReadWriteFileHandle::open(file.path()).unwrap();
+        handle.set_len(1 << 20).unwrap();
+        handle.punch_hole(0, 1 << 20).ok();
+        handle.write_all_at(0, &[0xAA; 4096]).unwrap();
+        handle.write_all_at(512 * 1024, &[0xAA; 4096]).unwrap();
+
+        let extents: Vec<Extent> = handle.extents().unwrap().map(Result::unwrap).collect();
+        let mut position = 0;
+        for extent in &extents {
+            assert_eq!(extent.offset, position);
+            assert!(extent.len > 0);
+            position += extent.len;
+        }
+        assert_eq!(position, 1 << 20);
+
+        // Written regions must always be reported as data.
+        for offset in [0, 512 * 1024] {
+            let extent = extents
+                .iter()
+                .find(|x| x.offset <= offset && offset < x.offset + x.len)
+                .unwrap();
+            assert_eq!(extent.kind, ExtentKind::Data);
+        }
+    }
+
+    #[test]
+    fn empty_file_has_no_extents() {
+        let file = NamedTempFile::new().unwrap();
+        let handle = ReadOnlyFileHandle::open(file.path()).unwrap();
+        assert_eq!(handle.extents().unwrap().count(), 0);
+    }
+
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn physical_extents_cover_written_data() {
+        let file = NamedTempFile::new().unwrap();
+        let handle = ReadWriteFileHandle::open(file.path()).unwrap();
+        handle.write_all_at(0, &[0xAA; 8192]).unwrap();
+        handle.sync_all().unwrap();
+
+        let extents = match handle.physical_extents() {
+            Ok(extents) => extents,
+            Err(HandleIoError::Unsupported) => return,
+            Err(e) => panic!("{e:?}"),
+        };
+        let mapped: u64 = extents.iter().map(|x| x.len).sum();
+        assert!(mapped >= 8192);
+    }
+}
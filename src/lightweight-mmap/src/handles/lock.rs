@@ -0,0 +1,184 @@
+use super::*;
+use core::marker::PhantomData;
+#[cfg(target_os = "windows")]
+use windows_sys::Win32::Foundation::HANDLE;
+
+/// The kind of advisory lock to take on a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockKind {
+    /// Any number of shared locks may be held at once, but not alongside an exclusive lock.
+    Shared,
+    /// Only one exclusive lock may be held at once, and no shared locks alongside it.
+    Exclusive,
+}
+
+/// A lock on a file (or a byte range of it), released when the guard is dropped.
+///
+/// Returned by the `lock_*` and `try_lock_*` methods of [`ReadOnlyFileHandle`] and
+/// [`ReadWriteFileHandle`].
+///
+/// Locks are advisory on Unix: they only coordinate processes which also take locks, and
+/// do not stop anyone from reading or writing the file. On Windows, locks are mandatory
+/// for reads and writes through other handles, but do not affect memory mappings.
+#[must_use = "the lock is released as soon as the guard is dropped"]
+pub struct FileLockGuard<'a> {
+    #[cfg(unix)]
+    fd: libc::c_int,
+    #[cfg(target_os = "windows")]
+    handle: HANDLE,
+    range: Option<(u64, u64)>,
+    _phantom: PhantomData<&'a ()>,
+}
+
+impl FileLockGuard<'_> {
+    /// Takes a lock on the whole file (`range` of `None`) or on a byte range of it.
+    #[cfg(unix)]
+    pub(crate) fn acquire(
+        fd: libc::c_int,
+        range: Option<(u64, u64)>,
+        kind: LockKind,
+        blocking: bool,
+    ) -> Result<Self, HandleIoError> {
+        match range {
+            Some((offset, len)) => unix_common::lock_range(fd, offset, len, Some(kind), blocking)?,
+            None => unix_common::lock_file(fd, kind, blocking)?,
+        }
+
+        Ok(FileLockGuard {
+            fd,
+            range,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Takes a lock on the whole file (`range` of `None`) or on a byte range of it.
+    #[cfg(target_os = "windows")]
+    pub(crate) fn acquire(
+        handle: HANDLE,
+        range: Option<(u64, u64)>,
+        kind: LockKind,
+        blocking: bool,
+    ) -> Result<Self, HandleIoError> {
+        let (offset, len) = range.unwrap_or((0, 0));
+        windows_common::lock_range(handle, offset, len, Some(kind), blocking)?;
+
+        Ok(FileLockGuard {
+            handle,
+            range,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Returns the locked byte range as `(offset, len)`, or `None` if the whole file is locked.
+    pub fn range(&self) -> Option<(u64, u64)> {
+        self.range
+    }
+}
+
+impl Drop for FileLockGuard<'_> {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        let _ = match self.range {
+            Some((offset, len)) => unix_common::lock_range(self.fd, offset, len, None, false),
+            None => unix_common::unlock_file(self.fd),
+        };
+
+        #[cfg(target_os = "windows")]
+        {
+            let (offset, len) = self.range.unwrap_or((0, 0));
+            let _ = windows_common::lock_range(self.handle, offset, len, None, false);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn exclusive_lock_excludes_other_handles() {
+        let file = NamedTempFile::new().unwrap();
+        let a = ReadWriteFileHandle::open(file.path()).unwrap();
+        let b = ReadOnlyFileHandle::open(file.path()).unwrap();
+
+        let guard = a.try_lock_exclusive().unwrap();
+        assert!(matches!(
+            b.try_lock_shared(),
+            Err(HandleIoError::WouldBlock)
+        ));
+        drop(guard);
+
+        let _shared = b.try_lock_shared().unwrap();
+        let _also_shared = a.try_lock_shared().unwrap();
+        assert!(matches!(
+            ReadOnlyFileHandle::open(file.path())
+                .unwrap()
+                .try_lock_exclusive(),
+            Err(HandleIoError::WouldBlock)
+        ));
+    }
+
+    #[test]
+    fn unlock_releases_forgotten_lock() {
+        let file = NamedTempFile::new().unwrap();
+        let a = ReadWriteFileHandle::open(file.path()).unwrap();
+        let b = ReadWriteFileHandle::open(file.path()).unwrap();
+
+        core::mem::forget(a.lock_exclusive().unwrap());
+        assert!(matches!(
+            b.try_lock_exclusive(),
+            Err(HandleIoError::WouldBlock)
+        ));
+
+        a.unlock().unwrap();
+        let _guard = b.try_lock_exclusive().unwrap();
+    }
+
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "windows"))]
+    fn range_locks_only_conflict_when_overlapping() {
+        let file = NamedTempFile::new().unwrap();
+        let a = ReadWriteFileHandle::create_preallocated(file.path(), 8192).unwrap();
+        let b = ReadWriteFileHandle::open(file.path()).unwrap();
+
+        let guard = a.lock_range(0, 4096, LockKind::Exclusive).unwrap();
+        assert_eq!(guard.range(), Some((0, 4096)));
+
+        let _other = b.try_lock_range(4096, 4096, LockKind::Exclusive).unwrap();
+        assert!(matches!(
+            b.try_lock_range(1024, 1024, LockKind::Shared),
+            Err(HandleIoError::WouldBlock)
+        ));
+
+        drop(guard);
+        let _now = b.try_lock_range(1024, 1024, LockKind::Shared).unwrap();
+    }
+
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "windows"))]
+    fn range_locks_past_4_gib_do_not_wrap() {
+        let file = NamedTempFile::new().unwrap();
+        let a = ReadWriteFileHandle::open(file.path()).unwrap();
+        let b = ReadWriteFileHandle::open(file.path()).unwrap();
+
+        let _guard = a.lock_range(1 << 32, 4096, LockKind::Exclusive).unwrap();
+        let _low = b.try_lock_range(0, 4096, LockKind::Exclusive).unwrap();
+        assert!(matches!(
+            b.try_lock_range((1 << 32) + 1024, 1024, LockKind::Shared),
+            Err(HandleIoError::WouldBlock)
+        ));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn range_lock_rejects_offsets_past_i64_max() {
+        let file = NamedTempFile::new().unwrap();
+        let handle = ReadWriteFileHandle::open(file.path()).unwrap();
+
+        assert!(matches!(
+            handle.try_lock_range(u64::MAX, 1, LockKind::Exclusive),
+            Err(HandleIoError::FailedToLock(libc::EOVERFLOW))
+        ));
+    }
+}
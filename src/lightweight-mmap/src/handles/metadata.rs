@@ -0,0 +1,106 @@
+/// A point in time, as seconds and nanoseconds since the Unix epoch (1970-01-01 00:00:00 UTC).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileTimestamp {
+    /// Whole seconds since the Unix epoch. Negative for times before it.
+    pub secs: i64,
+    /// Nanoseconds past `secs`, in `0..1_000_000_000`.
+    pub nanos: u32,
+}
+
+/// Metadata of an open file, as returned by [`ReadOnlyFileHandle::metadata`] and
+/// [`ReadWriteFileHandle::metadata`].
+///
+/// On Unix this comes from `statx` (Linux, Android) or `fstat`; on Windows from
+/// `GetFileInformationByHandle(Ex)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileMetadata {
+    /// The size of the file in bytes.
+    pub size: u64,
+    /// The number of 512-byte blocks allocated to the file. `blocks * 512` smaller than
+    /// `size` implies a sparse file.
+    pub blocks: u64,
+    /// The preferred block size for I/O on the file. `0` where unknown (Windows).
+    pub block_size: u64,
+    /// The file type and permission bits (`st_mode`) on Unix, or the file attributes on Windows.
+    pub mode: u32,
+    /// The device (or, on Windows, volume serial number) the file resides on.
+    pub device: u64,
+    /// The inode (or, on Windows, file index) of the file, unique within `device`.
+    pub inode: u64,
+    /// When the contents of the file were last modified.
+    pub modified: FileTimestamp,
+    /// When the metadata of the file (e.g. permissions) last changed.
+    pub changed: FileTimestamp,
+    /// When the file was created, if the platform and filesystem report it.
+    pub created: Option<FileTimestamp>,
+}
+
+impl FileMetadata {
+    /// Returns `true` if both metadata describe the same file, i.e. share `device` and `inode`.
+    ///
+    /// This holds across different paths (e.g. hard links) and separately opened handles.
+    pub fn same_file(&self, other: &FileMetadata) -> bool {
+        self.device == other.device && self.inode == other.inode
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use crate::handles::*;
+    use std::fs::{hard_link, metadata};
+    use std::time::UNIX_EPOCH;
+    use tempfile::{NamedTempFile, TempDir};
+
+    #[test]
+    fn metadata_matches_std() {
+        let file = NamedTempFile::new().unwrap();
+        let handle = ReadWriteFileHandle::open(file.path()).unwrap();
+        handle.write_all_at(0, &[0xAA; 5000]).unwrap();
+
+        let ours = handle.metadata().unwrap();
+        let theirs = metadata(file.path()).unwrap();
+        assert_eq!(ours.size, 5000);
+        assert!(ours.blocks > 0);
+
+        let modified = theirs
+            .modified()
+            .unwrap()
+            .duration_since(UNIX_EPOCH)
+            .unwrap();
+        assert_eq!(ours.modified.secs, modified.as_secs() as i64);
+        assert_eq!(ours.modified.nanos, modified.subsec_nanos());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            assert_eq!(ours.device, theirs.dev());
+            assert_eq!(ours.inode, theirs.ino());
+            assert_eq!(ours.mode, theirs.mode());
+            assert_eq!(ours.block_size, theirs.blksize());
+            assert_eq!(ours.changed.secs, theirs.ctime());
+        }
+    }
+
+    #[test]
+    fn same_file_compares_identity() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("a.bin");
+        let link = dir.path().join("b.bin");
+        let other = dir.path().join("c.bin");
+        std::fs::write(&path, b"Hello").unwrap();
+        std::fs::write(&other, b"Hello").unwrap();
+        hard_link(&path, &link).unwrap();
+
+        let a = ReadOnlyFileHandle::open(&path).unwrap().metadata().unwrap();
+        let b = ReadWriteFileHandle::open(&link)
+            .unwrap()
+            .metadata()
+            .unwrap();
+        let c = ReadOnlyFileHandle::open(&other)
+            .unwrap()
+            .metadata()
+            .unwrap();
+        assert!(a.same_file(&b));
+        assert!(!a.same_file(&c));
+    }
+}
@@ -1,12 +1,73 @@
+pub mod atomic;
+pub mod copy;
+#[cfg(feature = "std")]
+pub mod cursor;
+#[cfg(unix)]
+pub mod dir;
 pub mod error;
+pub mod extents;
+pub mod lock;
+pub mod metadata;
+pub mod options;
 pub mod readonly;
 pub mod readwrite;
+#[cfg(feature = "std")]
+pub mod vectored;
 
 #[cfg(unix)]
 pub mod unix_common;
 #[cfg(target_os = "windows")]
 pub mod windows_common;
 
+pub use atomic::*;
+pub use copy::*;
+#[cfg(feature = "std")]
+pub use cursor::*;
+#[cfg(unix)]
+pub use dir::*;
 pub use error::*;
+pub use extents::*;
+pub use lock::*;
+pub use metadata::*;
+pub use options::*;
 pub use readonly::*;
 pub use readwrite::*;
+#[cfg(feature = "std")]
+pub use vectored::*;
+
+use bitflags::bitflags;
+
+bitflags! {
+    /// Controls which parts of writeback [`ReadWriteFileHandle::sync_range`] performs.
+    /// These may be combined using bitwise operations.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct SyncRangeFlags: u32 {
+        /// Wait for any writeback of the range which is already in progress
+        const WAIT_BEFORE = 0b001;
+        /// Start writeback of any dirty pages in the range
+        const WRITE = 0b010;
+        /// Wait for the writeback of the range to complete
+        const WAIT_AFTER = 0b100;
+    }
+}
+
+/// Access pattern hints for a range of a file, given to the operating system with
+/// [`ReadOnlyFileHandle::advise`] and [`ReadWriteFileHandle::advise`].
+///
+/// These affect the page cache for the file itself, so apply to both regular reads and
+/// memory mappings of it. See `MemoryAdvice` for hints on a single mapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileAdvice {
+    /// No particular access pattern; undoes earlier advice
+    Normal,
+    /// The range will be read sequentially from lower to higher offsets
+    Sequential,
+    /// The range will be read in random order
+    Random,
+    /// The range will be needed soon, so should be read into the cache
+    WillNeed,
+    /// The range will not be needed again soon, so may be evicted from the cache
+    DontNeed,
+    /// The range will be read only once
+    NoReuse,
+}
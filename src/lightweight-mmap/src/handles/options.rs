@@ -0,0 +1,443 @@
+use super::*;
+
+/// Default file permissions for newly created files: rw-r--r-- (644)
+pub const DEFAULT_FILE_MODE: u32 = 0o644;
+
+/// Options used to configure how a file handle is opened.
+///
+/// This is a lightweight equivalent of [`std::fs::OpenOptions`] which produces
+/// [`ReadOnlyFileHandle`] and [`ReadWriteFileHandle`] directly, without going
+/// through `std`.
+///
+/// ```rust,no_run
+/// use lightweight_mmap::FileHandleOptions;
+///
+/// let handle = FileHandleOptions::new()
+///     .create_new(true)
+///     .mode(0o600)
+///     .open_read_write("new_file.bin")
+///     .unwrap();
+/// ```
+///
+/// **Note:** `mode`, `no_follow`, `no_atime`, `close_on_exec` and `resolve_beneath` only have an effect
+/// on Unix. `append` only has an effect on Unix, and only for read-write handles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileHandleOptions {
+    create: bool,
+    create_new: bool,
+    truncate: bool,
+    append: bool,
+    mode: u32,
+    no_follow: bool,
+    no_atime: bool,
+    close_on_exec: bool,
+    resolve_beneath: bool,
+}
+
+impl Default for FileHandleOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FileHandleOptions {
+    /// Creates a blank set of options, which opens an existing file.
+    pub const fn new() -> Self {
+        FileHandleOptions {
+            create: false,
+            create_new: false,
+            truncate: false,
+            append: false,
+            mode: DEFAULT_FILE_MODE,
+            no_follow: false,
+            no_atime: false,
+            close_on_exec: false,
+            resolve_beneath: false,
+        }
+    }
+
+    /// Creates the file if it does not exist (`O_CREAT`).
+    pub const fn create(mut self, create: bool) -> Self {
+        self.create = create;
+        self
+    }
+
+    /// Creates the file, failing if it already exists (`O_CREAT | O_EXCL`).
+    ///
+    /// When set, [`Self::create`] and [`Self::truncate`] are ignored.
+    pub const fn create_new(mut self, create_new: bool) -> Self {
+        self.create_new = create_new;
+        self
+    }
+
+    /// Truncates an existing file to zero length on open (`O_TRUNC`).
+    ///
+    /// Only honoured by [`Self::open_read_write`].
+    pub const fn truncate(mut self, truncate: bool) -> Self {
+        self.truncate = truncate;
+        self
+    }
+
+    /// Opens the file in append mode (`O_APPEND`).
+    ///
+    /// Only honoured by [`Self::open_read_write`].
+    pub const fn append(mut self, append: bool) -> Self {
+        self.append = append;
+        self
+    }
+
+    /// Sets the permission bits used when a new file is created.
+    /// Defaults to [`DEFAULT_FILE_MODE`]. The process umask still applies.
+    pub const fn mode(mut self, mode: u32) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Fails if the final path component is a symbolic link (`O_NOFOLLOW`).
+    pub const fn no_follow(mut self, no_follow: bool) -> Self {
+        self.no_follow = no_follow;
+        self
+    }
+
+    /// Does not update the file's last access time on reads (`O_NOATIME`).
+    ///
+    /// Linux and Android only; ignored elsewhere.
+    pub const fn no_atime(mut self, no_atime: bool) -> Self {
+        self.no_atime = no_atime;
+        self
+    }
+
+    /// Closes the handle automatically when the process calls `exec` (`O_CLOEXEC`).
+    pub const fn close_on_exec(mut self, close_on_exec: bool) -> Self {
+        self.close_on_exec = close_on_exec;
+        self
+    }
+
+    /// Refuses to open anything outside of the directory passed to the `_at` methods,
+    /// such as [`Self::open_read_write_at`].
+    ///
+    /// Absolute paths, `..` components and symbolic links anywhere in the path fail with
+    /// [`HandleOpenError::PathNotBeneathRoot`]. Uses `openat2` with `RESOLVE_BENEATH` on
+    /// Linux, falling back to opening one component at a time with `O_NOFOLLOW`.
+    ///
+    /// Unix only; has no effect on methods which do not take a `DirHandle`.
+    pub const fn resolve_beneath(mut self, resolve_beneath: bool) -> Self {
+        self.resolve_beneath = resolve_beneath;
+        self
+    }
+
+    /// Opens a read-only file handle using these options.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the file to open.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the file cannot be opened.
+    #[cfg(feature = "std")]
+    pub fn open_read_only<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+    ) -> Result<ReadOnlyFileHandle, HandleOpenError> {
+        ReadOnlyFileHandle::open_with_options(path.as_ref(), self)
+    }
+
+    /// Opens a read-only file handle using these options.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the file to open.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the file cannot be opened.
+    #[cfg(not(feature = "std"))]
+    pub fn open_read_only(&self, path: &str) -> Result<ReadOnlyFileHandle, HandleOpenError> {
+        ReadOnlyFileHandle::open_with_options(path, self)
+    }
+
+    /// Opens a read-write file handle using these options.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the file to open.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the file cannot be opened.
+    #[cfg(feature = "std")]
+    pub fn open_read_write<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+    ) -> Result<ReadWriteFileHandle, HandleOpenError> {
+        ReadWriteFileHandle::open_with_options(path.as_ref(), self)
+    }
+
+    /// Opens a read-write file handle using these options.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the file to open.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the file cannot be opened.
+    #[cfg(not(feature = "std"))]
+    pub fn open_read_write(&self, path: &str) -> Result<ReadWriteFileHandle, HandleOpenError> {
+        ReadWriteFileHandle::open_with_options(path, self)
+    }
+
+    /// Opens a read-only file handle relative to an open directory, using these options.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory `path` is relative to.
+    /// * `path` - The path to the file to open, relative to `dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the file cannot be opened.
+    #[cfg(all(unix, feature = "std"))]
+    pub fn open_read_only_at<P: AsRef<std::path::Path>>(
+        &self,
+        dir: &DirHandle,
+        path: P,
+    ) -> Result<ReadOnlyFileHandle, HandleOpenError> {
+        use std::os::unix::ffi::OsStrExt;
+
+        ReadOnlyFileHandle::open_at_with_options(dir, path.as_ref().as_os_str().as_bytes(), self)
+    }
+
+    /// Opens a read-only file handle relative to an open directory, using these options.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory `path` is relative to.
+    /// * `path` - The path to the file to open, relative to `dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the file cannot be opened.
+    #[cfg(all(unix, not(feature = "std")))]
+    pub fn open_read_only_at(
+        &self,
+        dir: &DirHandle,
+        path: &str,
+    ) -> Result<ReadOnlyFileHandle, HandleOpenError> {
+        ReadOnlyFileHandle::open_at_with_options(dir, path.as_bytes(), self)
+    }
+
+    /// Opens a read-write file handle relative to an open directory, using these options.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory `path` is relative to.
+    /// * `path` - The path to the file to open, relative to `dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the file cannot be opened.
+    #[cfg(all(unix, feature = "std"))]
+    pub fn open_read_write_at<P: AsRef<std::path::Path>>(
+        &self,
+        dir: &DirHandle,
+        path: P,
+    ) -> Result<ReadWriteFileHandle, HandleOpenError> {
+        use std::os::unix::ffi::OsStrExt;
+
+        ReadWriteFileHandle::open_at_with_options(dir, path.as_ref().as_os_str().as_bytes(), self)
+    }
+
+    /// Opens a read-write file handle relative to an open directory, using these options.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory `path` is relative to.
+    /// * `path` - The path to the file to open, relative to `dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the file cannot be opened.
+    #[cfg(all(unix, not(feature = "std")))]
+    pub fn open_read_write_at(
+        &self,
+        dir: &DirHandle,
+        path: &str,
+    ) -> Result<ReadWriteFileHandle, HandleOpenError> {
+        ReadWriteFileHandle::open_at_with_options(dir, path.as_bytes(), self)
+    }
+
+    /// Returns the file creation mode bits.
+    #[cfg(unix)]
+    pub(crate) fn file_mode(&self) -> u32 {
+        self.mode
+    }
+
+    /// Returns whether paths must resolve beneath the directory they are opened relative to.
+    #[cfg(unix)]
+    pub(crate) fn resolves_beneath(&self) -> bool {
+        self.resolve_beneath
+    }
+
+    /// Converts the options into `open` flags.
+    ///
+    /// # Arguments
+    ///
+    /// * `access` - Access mode (O_RDONLY or O_RDWR)
+    #[cfg(unix)]
+    pub(crate) fn unix_flags(&self, access: libc::c_int) -> libc::c_int {
+        use libc::{O_APPEND, O_CLOEXEC, O_CREAT, O_EXCL, O_NOFOLLOW, O_RDONLY, O_TRUNC};
+
+        let mut flags = access;
+        if self.create_new {
+            flags |= O_CREAT | O_EXCL;
+        } else {
+            if self.create {
+                flags |= O_CREAT;
+            }
+            if self.truncate && access != O_RDONLY {
+                flags |= O_TRUNC;
+            }
+        }
+
+        if self.append && access != O_RDONLY {
+            flags |= O_APPEND;
+        }
+        if self.no_follow {
+            flags |= O_NOFOLLOW;
+        }
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        if self.no_atime {
+            flags |= libc::O_NOATIME;
+        }
+        if self.close_on_exec {
+            flags |= O_CLOEXEC;
+        }
+
+        flags
+    }
+
+    /// Converts the options into a [`CreateFileW`] creation disposition.
+    ///
+    /// # Arguments
+    ///
+    /// * `writable` - Whether the handle is opened for writing.
+    ///
+    /// [`CreateFileW`]: windows_sys::Win32::Storage::FileSystem::CreateFileW
+    #[cfg(target_os = "windows")]
+    pub(crate) fn creation_disposition(&self, writable: bool) -> u32 {
+        use windows_sys::Win32::Storage::FileSystem::*;
+
+        let truncate = self.truncate && writable;
+        if self.create_new {
+            CREATE_NEW
+        } else if self.create && truncate {
+            CREATE_ALWAYS
+        } else if self.create {
+            OPEN_ALWAYS
+        } else if truncate {
+            TRUNCATE_EXISTING
+        } else {
+            OPEN_EXISTING
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::{NamedTempFile, TempDir};
+
+    #[test]
+    fn open_fails_on_missing_file_without_create() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("missing.bin");
+
+        assert!(FileHandleOptions::new().open_read_only(&path).is_err());
+        assert!(FileHandleOptions::new().open_read_write(&path).is_err());
+    }
+
+    #[test]
+    fn create_creates_missing_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("created.bin");
+
+        let handle = FileHandleOptions::new()
+            .create(true)
+            .open_read_write(&path)
+            .unwrap();
+        assert_eq!(handle.size().unwrap(), 0);
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn create_new_fails_on_existing_file() {
+        let file = NamedTempFile::new().unwrap();
+
+        let result = FileHandleOptions::new()
+            .create_new(true)
+            .open_read_write(file.path());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn truncate_clears_existing_file() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"Hello, World!").unwrap();
+        file.flush().unwrap();
+
+        let handle = FileHandleOptions::new()
+            .truncate(true)
+            .open_read_write(file.path())
+            .unwrap();
+        assert_eq!(handle.size().unwrap(), 0);
+    }
+
+    #[test]
+    fn truncate_is_ignored_for_read_only() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"Hello, World!").unwrap();
+        file.flush().unwrap();
+
+        let handle = FileHandleOptions::new()
+            .truncate(true)
+            .open_read_only(file.path())
+            .unwrap();
+        assert_eq!(handle.size().unwrap(), 13);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn mode_is_applied_to_new_files() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("private.bin");
+
+        FileHandleOptions::new()
+            .create(true)
+            .mode(0o600)
+            .open_read_write(&path)
+            .unwrap();
+
+        let mode = std::fs::metadata(&path).unwrap().permissions();
+        assert_eq!(mode.mode() & 0o777, 0o600);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn no_follow_rejects_symlinks() {
+        let file = NamedTempFile::new().unwrap();
+        let dir = TempDir::new().unwrap();
+        let link = dir.path().join("link.bin");
+        std::os::unix::fs::symlink(file.path(), &link).unwrap();
+
+        assert!(FileHandleOptions::new().open_read_only(&link).is_ok());
+        assert!(FileHandleOptions::new()
+            .no_follow(true)
+            .open_read_only(&link)
+            .is_err());
+    }
+}
@@ -58,6 +58,30 @@ impl ReadOnlyFileHandle {
         Ok(ReadOnlyFileHandle { inner })
     }
 
+    /// Opens a file using the given [`FileHandleOptions`].
+    ///
+    /// Use [`FileHandleOptions::open_read_only`] instead.
+    #[cfg(feature = "std")]
+    pub(crate) fn open_with_options(
+        path: &std::path::Path,
+        options: &FileHandleOptions,
+    ) -> Result<Self, HandleOpenError> {
+        let inner = InnerHandle::open_with_options(path, options)?;
+        Ok(ReadOnlyFileHandle { inner })
+    }
+
+    /// Opens a file using the given [`FileHandleOptions`].
+    ///
+    /// Use [`FileHandleOptions::open_read_only`] instead.
+    #[cfg(not(feature = "std"))]
+    pub(crate) fn open_with_options(
+        path: &str,
+        options: &FileHandleOptions,
+    ) -> Result<Self, HandleOpenError> {
+        let inner = InnerHandle::open_with_options(path, options)?;
+        Ok(ReadOnlyFileHandle { inner })
+    }
+
     /// Returns a reference to the underlying file descriptor or handle.
     ///
     /// This can be used for further operations if needed.
@@ -84,6 +108,362 @@ impl ReadOnlyFileHandle {
             windows_common::get_file_size(self.inner.handle())
         }
     }
+
+    /// Returns the metadata of the file, such as its size, identity and timestamps.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleIoError`] if the metadata cannot be queried.
+    pub fn metadata(&self) -> Result<FileMetadata, HandleIoError> {
+        #[cfg(unix)]
+        {
+            unix_common::get_metadata(self.inner.fd())
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            windows_common::get_metadata(self.inner.handle())
+        }
+    }
+
+    /// Returns an iterator over the data and hole [`Extent`]s of the file, in order.
+    ///
+    /// Useful for copying or scanning sparse files without reading through their holes.
+    /// Uses `SEEK_DATA`/`SEEK_HOLE`; where these are unavailable (including Windows), the
+    /// whole file is reported as a single [`ExtentKind::Data`] extent.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the size of the file cannot be determined.
+    /// Errors while seeking are returned by the iterator.
+    pub fn extents(&self) -> Result<Extents<'_>, HandleOpenError> {
+        let size = self.size()?;
+
+        #[cfg(unix)]
+        {
+            Ok(Extents::new(self.inner.fd(), size))
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            Ok(Extents::new(size))
+        }
+    }
+
+    /// Returns how the file is laid out on disk, using the `FIEMAP` ioctl.
+    ///
+    /// Holes are not reported, so the returned extents may have gaps between them.
+    ///
+    /// **Note:** Linux and Android only.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`HandleIoError::Unsupported`] if the filesystem does not support `FIEMAP`,
+    /// or another [`HandleIoError`] if the query fails.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn physical_extents(&self) -> Result<alloc::vec::Vec<PhysicalExtent>, HandleIoError> {
+        unix_common::fiemap(self.inner.fd())
+    }
+
+    /// Gives the operating system a hint about how a range of the file will be accessed,
+    /// e.g. to warm the page cache before mapping a region, or evict it after streaming through.
+    ///
+    /// Uses `posix_fadvise` where available. On Apple platforms only [`FileAdvice::WillNeed`]
+    /// has an effect, and on Windows the hint is ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The offset into the file where the range starts.
+    /// * `len` - The length of the range in bytes; 0 covers everything from `offset` onwards.
+    /// * `advice` - The expected access pattern.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleIoError`] if the hint is rejected, e.g. because the range is invalid.
+    pub fn advise(&self, offset: u64, len: u64, advice: FileAdvice) -> Result<(), HandleIoError> {
+        #[cfg(unix)]
+        {
+            unix_common::advise(self.inner.fd(), offset, len, advice)
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            let _ = (offset, len, advice);
+            Ok(())
+        }
+    }
+
+    /// Starts reading a range of the file into the page cache in the background.
+    ///
+    /// Uses `readahead` on Linux, and is equivalent to [`Self::advise`] with
+    /// [`FileAdvice::WillNeed`] on other Unix platforms. Ignored on Windows.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The offset into the file where the range starts.
+    /// * `len` - The length of the range in bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleIoError`] if the request is rejected.
+    pub fn readahead(&self, offset: u64, len: u64) -> Result<(), HandleIoError> {
+        #[cfg(unix)]
+        {
+            unix_common::readahead(self.inner.fd(), offset, len)
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            let _ = (offset, len);
+            Ok(())
+        }
+    }
+
+    /// Takes a shared lock on the whole file, blocking until it is available.
+    ///
+    /// Any number of shared locks can be held at once, but not alongside an exclusive lock.
+    /// Uses `flock` on Unix and `LockFileEx` on Windows. The lock is released when the
+    /// returned guard is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleIoError`] if locking fails.
+    pub fn lock_shared(&self) -> Result<FileLockGuard<'_>, HandleIoError> {
+        self.lock_with(None, LockKind::Shared, true)
+    }
+
+    /// Takes an exclusive lock on the whole file, blocking until it is available.
+    ///
+    /// See [`Self::lock_shared`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleIoError`] if locking fails.
+    pub fn lock_exclusive(&self) -> Result<FileLockGuard<'_>, HandleIoError> {
+        self.lock_with(None, LockKind::Exclusive, true)
+    }
+
+    /// Takes a shared lock on the whole file, if it is available right away.
+    ///
+    /// See [`Self::lock_shared`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`HandleIoError::WouldBlock`] if the file is locked exclusively elsewhere,
+    /// or another [`HandleIoError`] if locking fails.
+    pub fn try_lock_shared(&self) -> Result<FileLockGuard<'_>, HandleIoError> {
+        self.lock_with(None, LockKind::Shared, false)
+    }
+
+    /// Takes an exclusive lock on the whole file, if it is available right away.
+    ///
+    /// See [`Self::lock_shared`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`HandleIoError::WouldBlock`] if the file is locked elsewhere,
+    /// or another [`HandleIoError`] if locking fails.
+    pub fn try_lock_exclusive(&self) -> Result<FileLockGuard<'_>, HandleIoError> {
+        self.lock_with(None, LockKind::Exclusive, false)
+    }
+
+    /// Releases a lock on the whole file held through this handle.
+    ///
+    /// Dropping the [`FileLockGuard`] does this automatically; this is only needed
+    /// if the guard was leaked, e.g. with [`core::mem::forget`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleIoError`] if unlocking fails.
+    pub fn unlock(&self) -> Result<(), HandleIoError> {
+        #[cfg(unix)]
+        {
+            unix_common::unlock_file(self.inner.fd())
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            windows_common::lock_range(self.inner.handle(), 0, 0, None, false)
+        }
+    }
+
+    /// Takes a lock on a byte range of the file, blocking until it is available.
+    ///
+    /// Locks on ranges which do not overlap do not conflict, so several processes can
+    /// each own a region of the same file. A `len` of 0 covers everything from `offset`
+    /// onwards, however large the file grows.
+    ///
+    /// Uses open file description locks (`F_OFD_SETLKW`) on Linux and Android, which belong
+    /// to this handle. Elsewhere on Unix, classic `fcntl` locks are used instead; these belong
+    /// to the process, so they never conflict within a process, and closing *any* handle
+    /// to the file releases them. Range locks are separate from whole-file locks on Unix;
+    /// avoid mixing the two on one file.
+    ///
+    /// On Unix, exclusive range locks require write access, so they cannot be taken
+    /// through a read-only handle.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The offset into the file where the range starts.
+    /// * `len` - The length of the range in bytes.
+    /// * `kind` - Whether to take a shared or an exclusive lock.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleIoError`] if locking fails, e.g. if `offset` or `len` exceeds
+    /// `i64::MAX` on Unix.
+    pub fn lock_range(
+        &self,
+        offset: u64,
+        len: u64,
+        kind: LockKind,
+    ) -> Result<FileLockGuard<'_>, HandleIoError> {
+        self.lock_with(Some((offset, len)), kind, true)
+    }
+
+    /// Takes a lock on a byte range of the file, if it is available right away.
+    ///
+    /// See [`Self::lock_range`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`HandleIoError::WouldBlock`] if an overlapping range is locked elsewhere,
+    /// or another [`HandleIoError`] if locking fails.
+    pub fn try_lock_range(
+        &self,
+        offset: u64,
+        len: u64,
+        kind: LockKind,
+    ) -> Result<FileLockGuard<'_>, HandleIoError> {
+        self.lock_with(Some((offset, len)), kind, false)
+    }
+
+    fn lock_with(
+        &self,
+        range: Option<(u64, u64)>,
+        kind: LockKind,
+        blocking: bool,
+    ) -> Result<FileLockGuard<'_>, HandleIoError> {
+        #[cfg(unix)]
+        {
+            FileLockGuard::acquire(self.inner.fd(), range, kind, blocking)
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            FileLockGuard::acquire(self.inner.handle(), range, kind, blocking)
+        }
+    }
+
+    /// Creates a new, independently owned handle to the same open file.
+    ///
+    /// Both handles share the underlying open file, so either can be used (or dropped)
+    /// without affecting the other.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the handle cannot be duplicated.
+    pub fn try_clone(&self) -> Result<Self, HandleOpenError> {
+        #[cfg(unix)]
+        {
+            let fd = unix_common::duplicate_fd(self.inner.fd())?;
+            Ok(unsafe { Self::from_fd(fd) })
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            let handle = windows_common::duplicate_handle(self.inner.handle(), None)?;
+            Ok(unsafe { Self::from_handle(handle) })
+        }
+    }
+
+    /// Reads bytes from the file at the given offset into `buf`.
+    ///
+    /// This is intended for reading small regions (e.g. headers) where mapping the file
+    /// would be wasteful. On Unix, it does not use or update a file position; on Windows,
+    /// the file pointer is moved to the end of the read.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The offset into the file to start reading from.
+    /// * `buf` - The buffer to read into.
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes read, which may be less than `buf.len()`.
+    /// A value of 0 indicates the end of the file was reached.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleIoError`] if the read fails.
+    pub fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<usize, HandleIoError> {
+        #[cfg(unix)]
+        {
+            unix_common::read_at(self.inner.fd(), offset, buf)
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            windows_common::read_at(self.inner.handle(), offset, buf)
+        }
+    }
+
+    /// Reads exactly `buf.len()` bytes from the file at the given offset.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The offset into the file to start reading from.
+    /// * `buf` - The buffer to fill.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`HandleIoError::UnexpectedEof`] if the end of the file is reached before
+    /// the buffer is filled, or another [`HandleIoError`] if the read fails.
+    pub fn read_exact_at(&self, mut offset: u64, mut buf: &mut [u8]) -> Result<(), HandleIoError> {
+        while !buf.is_empty() {
+            match self.read_at(offset, buf)? {
+                0 => return Err(HandleIoError::UnexpectedEof),
+                read => {
+                    buf = &mut buf[read..];
+                    offset += read as u64;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads from the file at the given offset into multiple buffers, filling them in order.
+    ///
+    /// Uses a single `preadv` call where available; otherwise falls back to one read per buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The offset into the file to start reading from.
+    /// * `bufs` - The buffers to read into.
+    ///
+    /// # Returns
+    ///
+    /// A [`VectoredTransfer`] describing how many bytes were read into each buffer.
+    /// Fewer bytes than the total buffer size may be read.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleIoError`] if the read fails.
+    #[cfg(feature = "std")]
+    pub fn read_vectored_at(
+        &self,
+        offset: u64,
+        bufs: &mut [std::io::IoSliceMut<'_>],
+    ) -> Result<VectoredTransfer, HandleIoError> {
+        #[cfg(unix)]
+        let total = unix_common::read_vectored_at(self.inner.fd(), offset, bufs)?;
+
+        #[cfg(target_os = "windows")]
+        let total = windows_common::read_vectored_at(self.inner.handle(), offset, bufs)?;
+
+        Ok(VectoredTransfer::new(total))
+    }
 }
 
 #[cfg(all(test, feature = "std"))]
@@ -170,4 +550,143 @@ mod tests {
         let size = handle.size().unwrap();
         assert_eq!(size, 13); // Length of "Hello, World!"
     }
+
+    #[test]
+    fn can_read_at_offset() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"Hello, World!").unwrap();
+        file.flush().unwrap();
+
+        let handle = ReadOnlyFileHandle::open(file.path()).unwrap();
+        let mut buf = [0u8; 5];
+        assert_eq!(handle.read_at(7, &mut buf).unwrap(), 5);
+        assert_eq!(&buf, b"World");
+    }
+
+    #[test]
+    fn read_at_returns_short_count_near_end_of_file() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"Hello, World!").unwrap();
+        file.flush().unwrap();
+
+        let handle = ReadOnlyFileHandle::open(file.path()).unwrap();
+        let mut buf = [0u8; 8];
+        assert_eq!(handle.read_at(10, &mut buf).unwrap(), 3);
+        assert_eq!(&buf[..3], b"ld!");
+        assert_eq!(handle.read_at(13, &mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn read_exact_at_fails_past_end_of_file() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"Hello, World!").unwrap();
+        file.flush().unwrap();
+
+        let handle = ReadOnlyFileHandle::open(file.path()).unwrap();
+        let mut buf = [0u8; 13];
+        handle.read_exact_at(0, &mut buf).unwrap();
+        assert_eq!(&buf, b"Hello, World!");
+
+        let mut buf = [0u8; 8];
+        assert_eq!(
+            handle.read_exact_at(10, &mut buf),
+            Err(HandleIoError::UnexpectedEof)
+        );
+    }
+
+    #[test]
+    fn can_read_vectored_at_offset() {
+        use std::io::IoSliceMut;
+
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"Hello, World!").unwrap();
+        file.flush().unwrap();
+
+        let handle = ReadOnlyFileHandle::open(file.path()).unwrap();
+        let (mut first, mut second) = ([0u8; 5], [0u8; 8]);
+        let mut bufs = [IoSliceMut::new(&mut first), IoSliceMut::new(&mut second)];
+
+        let transfer = handle.read_vectored_at(2, &mut bufs).unwrap();
+        assert_eq!(transfer.total(), 11);
+        assert_eq!(
+            transfer.per_buffer(&bufs).collect::<std::vec::Vec<_>>(),
+            [5, 6]
+        );
+        assert_eq!(&first, b"llo, ");
+        assert_eq!(&second[..6], b"World!");
+    }
+
+    #[test]
+    fn can_create_from_std_file() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"Hello, World!").unwrap();
+        file.flush().unwrap();
+
+        let std_file = std::fs::File::open(file.path()).unwrap();
+        let handle = ReadOnlyFileHandle::from(std_file);
+        assert_eq!(handle.size().unwrap(), 13);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn raw_fd_round_trip_keeps_descriptor_open() {
+        use std::os::fd::{AsRawFd, FromRawFd, IntoRawFd, OwnedFd};
+
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"Hello").unwrap();
+        file.flush().unwrap();
+
+        let handle = ReadOnlyFileHandle::open(file.path()).unwrap();
+        let raw = handle.as_raw_fd();
+        let fd = handle.into_raw_fd();
+        assert_eq!(raw, fd);
+
+        // Descriptor must still be usable after the handle is gone.
+        let handle = unsafe { ReadOnlyFileHandle::from_raw_fd(fd) };
+        assert_eq!(handle.size().unwrap(), 5);
+
+        let owned = OwnedFd::from(handle);
+        let handle = ReadOnlyFileHandle::from(owned);
+        assert_eq!(handle.size().unwrap(), 5);
+    }
+
+    #[test]
+    fn cloned_handle_outlives_original() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"Hello, World!").unwrap();
+        file.flush().unwrap();
+
+        let handle = ReadOnlyFileHandle::open(file.path()).unwrap();
+        let clone = handle.try_clone().unwrap();
+        drop(handle);
+
+        let mut buf = [0u8; 5];
+        clone.read_exact_at(0, &mut buf).unwrap();
+        assert_eq!(&buf, b"Hello");
+    }
+
+    #[test]
+    fn can_advise_and_read_ahead() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(&[0xAA; 64 * 1024]).unwrap();
+        file.flush().unwrap();
+
+        let handle = ReadOnlyFileHandle::open(file.path()).unwrap();
+        for advice in [
+            FileAdvice::Normal,
+            FileAdvice::Sequential,
+            FileAdvice::Random,
+            FileAdvice::WillNeed,
+            FileAdvice::DontNeed,
+            FileAdvice::NoReuse,
+        ] {
+            handle.advise(0, 0, advice).unwrap();
+        }
+        handle.advise(4096, 8192, FileAdvice::WillNeed).unwrap();
+        handle.readahead(0, 64 * 1024).unwrap();
+
+        let mut buf = [0u8; 4];
+        handle.read_exact_at(0, &mut buf).unwrap();
+        assert_eq!(buf, [0xAA; 4]);
+    }
 }
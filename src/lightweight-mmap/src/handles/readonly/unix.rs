@@ -1,6 +1,11 @@
 use super::*;
+use core::ffi::CStr;
 use libc::*;
-use unix_common::open_with_flags;
+#[cfg(feature = "std")]
+use std::os::unix::ffi::OsStrExt;
+use unix_common::{
+    open_cstr_with_flags, open_with_flags, openat_beneath_with_flags, openat_with_flags,
+};
 
 /// Unix platform-specific implementation for [`ReadOnlyFileHandle`].
 pub struct InnerHandle {
@@ -22,10 +27,7 @@ impl InnerHandle {
     /// Returns a [`HandleOpenError`] if the file cannot be opened.
     #[cfg(feature = "std")]
     pub fn open(path: &std::path::Path) -> Result<Self, HandleOpenError> {
-        let path_str = path.to_str().ok_or_else(|| {
-            HandleOpenError::failed_to_open_file_handle_unix(-1, "<invalid_utf8>")
-        })?;
-        let fd = open_with_flags(path_str, O_RDONLY)?;
+        let fd = open_with_flags(path.as_os_str().as_bytes(), O_RDONLY, DEFAULT_FILE_MODE)?;
         Ok(InnerHandle { fd })
     }
 
@@ -40,7 +42,69 @@ impl InnerHandle {
     /// Returns a [`HandleOpenError`] if the file cannot be opened.
     #[cfg(not(feature = "std"))]
     pub fn open(path: &str) -> Result<Self, HandleOpenError> {
-        let fd = open_with_flags(path, O_RDONLY)?;
+        let fd = open_with_flags(path.as_bytes(), O_RDONLY, DEFAULT_FILE_MODE)?;
+        Ok(InnerHandle { fd })
+    }
+
+    /// Opens the file with read-only access, using the given [`FileHandleOptions`].
+    #[cfg(feature = "std")]
+    pub fn open_with_options(
+        path: &std::path::Path,
+        options: &FileHandleOptions,
+    ) -> Result<Self, HandleOpenError> {
+        let fd = open_with_flags(
+            path.as_os_str().as_bytes(),
+            options.unix_flags(O_RDONLY),
+            options.file_mode(),
+        )?;
+        Ok(InnerHandle { fd })
+    }
+
+    /// Opens the file with read-only access, using the given [`FileHandleOptions`].
+    #[cfg(not(feature = "std"))]
+    pub fn open_with_options(
+        path: &str,
+        options: &FileHandleOptions,
+    ) -> Result<Self, HandleOpenError> {
+        let fd = open_with_flags(
+            path.as_bytes(),
+            options.unix_flags(O_RDONLY),
+            options.file_mode(),
+        )?;
+        Ok(InnerHandle { fd })
+    }
+
+    /// Opens the file with read-only access, from a path given as raw bytes.
+    #[cfg(not(feature = "std"))]
+    pub fn open_bytes(path: &[u8]) -> Result<Self, HandleOpenError> {
+        let fd = open_with_flags(path, O_RDONLY, DEFAULT_FILE_MODE)?;
+        Ok(InnerHandle { fd })
+    }
+
+    /// Opens the file with read-only access, from a NUL-terminated path, without allocating.
+    pub fn open_cstr(path: &CStr) -> Result<Self, HandleOpenError> {
+        let fd = open_cstr_with_flags(path, O_RDONLY, DEFAULT_FILE_MODE)?;
+        Ok(InnerHandle { fd })
+    }
+
+    /// Opens the file with read-only access relative to an open directory,
+    /// using the given [`FileHandleOptions`].
+    pub fn open_at(
+        dir_fd: c_int,
+        path: &[u8],
+        options: &FileHandleOptions,
+    ) -> Result<Self, HandleOpenError> {
+        let open = if options.resolves_beneath() {
+            openat_beneath_with_flags
+        } else {
+            openat_with_flags
+        };
+        let fd = open(
+            dir_fd,
+            path,
+            options.unix_flags(O_RDONLY),
+            options.file_mode(),
+        )?;
         Ok(InnerHandle { fd })
     }
 
@@ -48,6 +112,22 @@ impl InnerHandle {
     pub fn fd(&self) -> c_int {
         self.fd
     }
+
+    /// Creates the handle from an existing file descriptor, taking ownership of it.
+    ///
+    /// # Safety
+    ///
+    /// `fd` must be an open file descriptor which is not owned (closed) by anything else.
+    pub unsafe fn from_raw_fd(fd: c_int) -> Self {
+        InnerHandle { fd }
+    }
+
+    /// Consumes the handle, returning the file descriptor without closing it.
+    pub fn into_raw_fd(self) -> c_int {
+        let fd = self.fd;
+        core::mem::forget(self);
+        fd
+    }
 }
 
 impl Drop for InnerHandle {
@@ -57,3 +137,219 @@ impl Drop for InnerHandle {
         }
     }
 }
+
+impl ReadOnlyFileHandle {
+    /// Opens a file in read-only mode with shared access, relative to an open directory.
+    ///
+    /// The path is resolved from `dir` itself (`openat`), not from the directory's path.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory `path` is relative to.
+    /// * `path` - The path to the file to open, relative to `dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the file cannot be opened.
+    #[cfg(feature = "std")]
+    pub fn open_at<P: AsRef<std::path::Path>>(
+        dir: &DirHandle,
+        path: P,
+    ) -> Result<Self, HandleOpenError> {
+        let inner = InnerHandle::open_at(
+            dir.fd(),
+            path.as_ref().as_os_str().as_bytes(),
+            &FileHandleOptions::new(),
+        )?;
+        Ok(ReadOnlyFileHandle { inner })
+    }
+
+    /// Opens a file in read-only mode with shared access, relative to an open directory.
+    ///
+    /// The path is resolved from `dir` itself (`openat`), not from the directory's path.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory `path` is relative to.
+    /// * `path` - The path to the file to open, relative to `dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the file cannot be opened.
+    #[cfg(not(feature = "std"))]
+    pub fn open_at(dir: &DirHandle, path: &str) -> Result<Self, HandleOpenError> {
+        let inner = InnerHandle::open_at(dir.fd(), path.as_bytes(), &FileHandleOptions::new())?;
+        Ok(ReadOnlyFileHandle { inner })
+    }
+
+    /// Opens a file in read-only mode with shared access, refusing to resolve outside of `dir`.
+    ///
+    /// See [`FileHandleOptions::resolve_beneath`] for details.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The root directory `path` must stay beneath.
+    /// * `path` - The path to the file to open, relative to `dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`HandleOpenError::PathNotBeneathRoot`] if the path would escape `dir`,
+    /// or another [`HandleOpenError`] if the file cannot be opened.
+    #[cfg(feature = "std")]
+    pub fn open_beneath<P: AsRef<std::path::Path>>(
+        dir: &DirHandle,
+        path: P,
+    ) -> Result<Self, HandleOpenError> {
+        let options = FileHandleOptions::new().resolve_beneath(true);
+        let inner = InnerHandle::open_at(dir.fd(), path.as_ref().as_os_str().as_bytes(), &options)?;
+        Ok(ReadOnlyFileHandle { inner })
+    }
+
+    /// Opens a file in read-only mode with shared access, refusing to resolve outside of `dir`.
+    ///
+    /// See [`FileHandleOptions::resolve_beneath`] for details.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The root directory `path` must stay beneath.
+    /// * `path` - The path to the file to open, relative to `dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`HandleOpenError::PathNotBeneathRoot`] if the path would escape `dir`,
+    /// or another [`HandleOpenError`] if the file cannot be opened.
+    #[cfg(not(feature = "std"))]
+    pub fn open_beneath(dir: &DirHandle, path: &str) -> Result<Self, HandleOpenError> {
+        let options = FileHandleOptions::new().resolve_beneath(true);
+        let inner = InnerHandle::open_at(dir.fd(), path.as_bytes(), &options)?;
+        Ok(ReadOnlyFileHandle { inner })
+    }
+
+    /// Opens a file relative to an open directory, using the given [`FileHandleOptions`].
+    ///
+    /// Use [`FileHandleOptions::open_read_only_at`] instead.
+    pub(crate) fn open_at_with_options(
+        dir: &DirHandle,
+        path: &[u8],
+        options: &FileHandleOptions,
+    ) -> Result<Self, HandleOpenError> {
+        let inner = InnerHandle::open_at(dir.fd(), path, options)?;
+        Ok(ReadOnlyFileHandle { inner })
+    }
+
+    /// Opens a file in read-only mode with shared access, from a NUL-terminated path.
+    ///
+    /// The path is passed straight to `open`, so no allocation takes place on success.
+    /// Useful in `no_std` environments which already hold NUL-terminated paths.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the file to open.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the file cannot be opened.
+    pub fn open_cstr(path: &CStr) -> Result<Self, HandleOpenError> {
+        let inner = InnerHandle::open_cstr(path)?;
+        Ok(ReadOnlyFileHandle { inner })
+    }
+
+    /// Creates a handle which takes ownership of the given file descriptor.
+    ///
+    /// # Safety
+    ///
+    /// See [`InnerHandle::from_raw_fd`].
+    pub(crate) unsafe fn from_fd(fd: c_int) -> Self {
+        ReadOnlyFileHandle {
+            inner: InnerHandle::from_raw_fd(fd),
+        }
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl ReadOnlyFileHandle {
+    /// Opens a file in read-only mode with shared access, from a path given as raw bytes.
+    ///
+    /// Unix paths are arbitrary bytes, so this can open files whose names are not valid UTF-8.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the file to open.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the file cannot be opened.
+    pub fn open_bytes(path: &[u8]) -> Result<Self, HandleOpenError> {
+        let inner = InnerHandle::open_bytes(path)?;
+        Ok(ReadOnlyFileHandle { inner })
+    }
+
+    /// Creates a handle from an existing file descriptor, taking ownership of it.
+    ///
+    /// # Safety
+    ///
+    /// `fd` must be an open file descriptor with read-only access, which is not
+    /// owned (closed) by anything else.
+    pub unsafe fn from_raw_fd(fd: c_int) -> Self {
+        Self::from_fd(fd)
+    }
+
+    /// Consumes the handle, returning the file descriptor without closing it.
+    pub fn into_raw_fd(self) -> c_int {
+        self.inner.into_raw_fd()
+    }
+}
+
+#[cfg(feature = "std")]
+mod std_impls {
+    use super::*;
+    use std::fs::File;
+    use std::os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, OwnedFd, RawFd};
+
+    impl AsRawFd for ReadOnlyFileHandle {
+        fn as_raw_fd(&self) -> RawFd {
+            self.inner.fd()
+        }
+    }
+
+    impl AsFd for ReadOnlyFileHandle {
+        fn as_fd(&self) -> BorrowedFd<'_> {
+            unsafe { BorrowedFd::borrow_raw(self.inner.fd()) }
+        }
+    }
+
+    impl FromRawFd for ReadOnlyFileHandle {
+        /// Creates a handle from an existing file descriptor, taking ownership of it.
+        ///
+        /// The descriptor must have been opened with read-only access.
+        unsafe fn from_raw_fd(fd: RawFd) -> Self {
+            Self::from_fd(fd)
+        }
+    }
+
+    impl IntoRawFd for ReadOnlyFileHandle {
+        fn into_raw_fd(self) -> RawFd {
+            self.inner.into_raw_fd()
+        }
+    }
+
+    impl From<OwnedFd> for ReadOnlyFileHandle {
+        /// Takes ownership of a file descriptor opened with read-only access.
+        fn from(fd: OwnedFd) -> Self {
+            unsafe { Self::from_raw_fd(fd.into_raw_fd()) }
+        }
+    }
+
+    impl From<File> for ReadOnlyFileHandle {
+        /// Takes ownership of a [`File`] opened with read-only access.
+        fn from(file: File) -> Self {
+            OwnedFd::from(file).into()
+        }
+    }
+
+    impl From<ReadOnlyFileHandle> for OwnedFd {
+        fn from(handle: ReadOnlyFileHandle) -> Self {
+            unsafe { OwnedFd::from_raw_fd(handle.into_raw_fd()) }
+        }
+    }
+}
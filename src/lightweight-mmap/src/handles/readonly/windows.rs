@@ -2,7 +2,7 @@
 
 use super::windows_common::*;
 use crate::*;
-use handles::HandleOpenError;
+use handles::{FileHandleOptions, HandleOpenError};
 use windows_sys::Win32::{Foundation::*, Storage::FileSystem::*};
 
 #[cfg(feature = "mmap")]
@@ -41,10 +41,66 @@ impl InnerHandle {
         })
     }
 
+    /// Opens the file with appropriate access, using the given [`FileHandleOptions`].
+    #[cfg(feature = "std")]
+    pub fn open_with_options(
+        path: &std::path::Path,
+        options: &FileHandleOptions,
+    ) -> Result<Self, HandleOpenError> {
+        let handle = open_with_access(path, GENERIC_READ, options.creation_disposition(false))?;
+        Ok(InnerHandle {
+            handle,
+            #[cfg(feature = "mmap")]
+            mapping: UnsafeCell::new(INVALID_HANDLE_VALUE),
+        })
+    }
+
+    /// Opens the file with appropriate access, using the given [`FileHandleOptions`].
+    #[cfg(not(feature = "std"))]
+    pub fn open_with_options(
+        path: &str,
+        options: &FileHandleOptions,
+    ) -> Result<Self, HandleOpenError> {
+        let handle = open_with_access(path, GENERIC_READ, options.creation_disposition(false))?;
+        Ok(InnerHandle {
+            handle,
+            #[cfg(feature = "mmap")]
+            mapping: UnsafeCell::new(INVALID_HANDLE_VALUE),
+        })
+    }
+
     /// Returns the raw HANDLE.
     pub fn handle(&self) -> HANDLE {
         self.handle
     }
+
+    /// Creates the handle from an existing file HANDLE, taking ownership of it.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be an open file HANDLE which is not owned (closed) by anything else.
+    pub unsafe fn from_raw_handle(handle: HANDLE) -> Self {
+        InnerHandle {
+            handle,
+            #[cfg(feature = "mmap")]
+            mapping: UnsafeCell::new(INVALID_HANDLE_VALUE),
+        }
+    }
+
+    /// Consumes the handle, returning the file HANDLE without closing it.
+    pub fn into_raw_handle(self) -> HANDLE {
+        let this = core::mem::ManuallyDrop::new(self);
+
+        #[cfg(feature = "mmap")]
+        unsafe {
+            let mapping = *this.mapping.get();
+            if mapping != INVALID_HANDLE_VALUE {
+                CloseHandle(mapping);
+            }
+        }
+
+        this.handle
+    }
 }
 
 impl Drop for InnerHandle {
@@ -64,3 +120,90 @@ impl Drop for InnerHandle {
         }
     }
 }
+
+impl ReadOnlyFileHandle {
+    /// Creates a handle which takes ownership of the given file HANDLE.
+    ///
+    /// # Safety
+    ///
+    /// See [`InnerHandle::from_raw_handle`].
+    pub(crate) unsafe fn from_handle(handle: HANDLE) -> Self {
+        ReadOnlyFileHandle {
+            inner: InnerHandle::from_raw_handle(handle),
+        }
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl ReadOnlyFileHandle {
+    /// Creates a handle from an existing file HANDLE, taking ownership of it.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be an open file HANDLE with read-only access, which is not
+    /// owned (closed) by anything else.
+    pub unsafe fn from_raw_handle(handle: HANDLE) -> Self {
+        Self::from_handle(handle)
+    }
+
+    /// Consumes the handle, returning the file HANDLE without closing it.
+    pub fn into_raw_handle(self) -> HANDLE {
+        self.inner.into_raw_handle()
+    }
+}
+
+#[cfg(feature = "std")]
+mod std_impls {
+    use super::*;
+    use std::fs::File;
+    use std::os::windows::io::{
+        AsHandle, AsRawHandle, BorrowedHandle, FromRawHandle, IntoRawHandle, OwnedHandle, RawHandle,
+    };
+
+    impl AsRawHandle for ReadOnlyFileHandle {
+        fn as_raw_handle(&self) -> RawHandle {
+            self.inner.handle()
+        }
+    }
+
+    impl AsHandle for ReadOnlyFileHandle {
+        fn as_handle(&self) -> BorrowedHandle<'_> {
+            unsafe { BorrowedHandle::borrow_raw(self.inner.handle()) }
+        }
+    }
+
+    impl FromRawHandle for ReadOnlyFileHandle {
+        /// Creates a handle from an existing file HANDLE, taking ownership of it.
+        ///
+        /// The HANDLE must have been opened with read-only access.
+        unsafe fn from_raw_handle(handle: RawHandle) -> Self {
+            Self::from_handle(handle)
+        }
+    }
+
+    impl IntoRawHandle for ReadOnlyFileHandle {
+        fn into_raw_handle(self) -> RawHandle {
+            self.inner.into_raw_handle()
+        }
+    }
+
+    impl From<OwnedHandle> for ReadOnlyFileHandle {
+        /// Takes ownership of a file HANDLE opened with read-only access.
+        fn from(handle: OwnedHandle) -> Self {
+            unsafe { Self::from_raw_handle(handle.into_raw_handle()) }
+        }
+    }
+
+    impl From<File> for ReadOnlyFileHandle {
+        /// Takes ownership of a [`File`] opened with read-only access.
+        fn from(file: File) -> Self {
+            OwnedHandle::from(file).into()
+        }
+    }
+
+    impl From<ReadOnlyFileHandle> for OwnedHandle {
+        fn from(handle: ReadOnlyFileHandle) -> Self {
+            unsafe { OwnedHandle::from_raw_handle(handle.into_raw_handle()) }
+        }
+    }
+}
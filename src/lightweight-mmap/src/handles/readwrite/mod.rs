@@ -105,6 +105,30 @@ impl ReadWriteFileHandle {
         Ok(ReadWriteFileHandle { inner })
     }
 
+    /// Opens a file using the given [`FileHandleOptions`].
+    ///
+    /// Use [`FileHandleOptions::open_read_write`] instead.
+    #[cfg(feature = "std")]
+    pub(crate) fn open_with_options(
+        path: &std::path::Path,
+        options: &FileHandleOptions,
+    ) -> Result<Self, HandleOpenError> {
+        let inner = InnerHandle::open_with_options(path, options)?;
+        Ok(ReadWriteFileHandle { inner })
+    }
+
+    /// Opens a file using the given [`FileHandleOptions`].
+    ///
+    /// Use [`FileHandleOptions::open_read_write`] instead.
+    #[cfg(not(feature = "std"))]
+    pub(crate) fn open_with_options(
+        path: &str,
+        options: &FileHandleOptions,
+    ) -> Result<Self, HandleOpenError> {
+        let inner = InnerHandle::open_with_options(path, options)?;
+        Ok(ReadWriteFileHandle { inner })
+    }
+
     /// Returns a reference to the underlying file descriptor or handle.
     ///
     /// This can be used for further operations if needed.
@@ -124,6 +148,703 @@ impl ReadWriteFileHandle {
             windows_common::get_file_size(self.inner.handle())
         }
     }
+
+    /// Returns the metadata of the file, such as its size, identity and timestamps.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleIoError`] if the metadata cannot be queried.
+    pub fn metadata(&self) -> Result<FileMetadata, HandleIoError> {
+        #[cfg(unix)]
+        {
+            unix_common::get_metadata(self.inner.fd())
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            windows_common::get_metadata(self.inner.handle())
+        }
+    }
+
+    /// Sets the size of the file in bytes, growing or shrinking it as needed.
+    ///
+    /// When growing, disk space for the new region is reserved where the platform and
+    /// filesystem support it (see [`Self::allocate`]). When shrinking, any data past `size`
+    /// is discarded.
+    ///
+    /// Existing memory mappings are not resized; create a new mapping to access the new region.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - The new size of the file in bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the size cannot be changed.
+    pub fn set_len(&self, size: i64) -> Result<(), HandleOpenError> {
+        #[cfg(unix)]
+        {
+            unix_common::set_file_size(self.inner.fd(), size)
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            windows_common::set_file_size(self.inner.handle(), size)
+        }
+    }
+
+    /// Grows the file to at least `size` bytes. Never shrinks the file.
+    ///
+    /// On Linux (`fallocate`) and FreeBSD/DragonFly (`posix_fallocate`), disk space for the new
+    /// region is reserved up front, so later writes through a mapping cannot fail due to lack of
+    /// space. If the filesystem does not support this, the file is extended with `ftruncate`
+    /// instead. On other platforms the file is simply extended.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - The minimum size of the file in bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the size cannot be changed.
+    pub fn allocate(&self, size: i64) -> Result<(), HandleOpenError> {
+        if size <= self.size()? {
+            return Ok(());
+        }
+
+        #[cfg(unix)]
+        {
+            unix_common::grow_file(self.inner.fd(), size)
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            windows_common::set_file_size(self.inner.handle(), size)
+        }
+    }
+
+    /// Deallocates the given range of the file, giving its disk space back to the filesystem.
+    ///
+    /// The range reads back as zeros afterwards and the file size does not change. Uses
+    /// `fallocate` with `FALLOC_FL_PUNCH_HOLE | FALLOC_FL_KEEP_SIZE`. Filesystems may only free
+    /// whole blocks, zeroing partial blocks at either end of the range instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The offset into the file where the range starts.
+    /// * `len` - The length of the range in bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`HandleIoError::Unsupported`] on platforms other than Linux and Android,
+    /// or if the filesystem cannot punch holes. Returns another [`HandleIoError`] if the
+    /// operation fails.
+    pub fn punch_hole(&self, offset: u64, len: u64) -> Result<(), HandleIoError> {
+        #[cfg(unix)]
+        {
+            unix_common::punch_hole(self.inner.fd(), offset, len)
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            let _ = (offset, len);
+            Err(HandleIoError::Unsupported)
+        }
+    }
+
+    /// Zeroes the given range of the file, without writing the zeros out.
+    ///
+    /// Unlike [`Self::punch_hole`], disk space for the range stays allocated. The file size
+    /// does not change, even if the range extends past the end of the file. Uses `fallocate`
+    /// with `FALLOC_FL_ZERO_RANGE | FALLOC_FL_KEEP_SIZE`.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The offset into the file where the range starts.
+    /// * `len` - The length of the range in bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`HandleIoError::Unsupported`] on platforms other than Linux and Android,
+    /// or if the filesystem cannot zero ranges. Returns another [`HandleIoError`] if the
+    /// operation fails.
+    pub fn zero_range(&self, offset: u64, len: u64) -> Result<(), HandleIoError> {
+        #[cfg(unix)]
+        {
+            unix_common::zero_range(self.inner.fd(), offset, len)
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            let _ = (offset, len);
+            Err(HandleIoError::Unsupported)
+        }
+    }
+
+    /// Reserves disk space for the given range of the file, without changing the file size.
+    ///
+    /// Space reserved past the end of the file is used as the file is later grown (e.g. with
+    /// [`Self::set_len`] or writes), so those cannot fail due to lack of space. Uses `fallocate`
+    /// with `FALLOC_FL_KEEP_SIZE`.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The offset into the file where the range starts.
+    /// * `len` - The length of the range in bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`HandleIoError::Unsupported`] on platforms other than Linux and Android,
+    /// or if the filesystem cannot preallocate. Returns another [`HandleIoError`] if the
+    /// operation fails, such as when there is not enough space.
+    pub fn preallocate_keep_size(&self, offset: u64, len: u64) -> Result<(), HandleIoError> {
+        #[cfg(unix)]
+        {
+            unix_common::preallocate_keep_size(self.inner.fd(), offset, len)
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            let _ = (offset, len);
+            Err(HandleIoError::Unsupported)
+        }
+    }
+
+    /// Returns an iterator over the data and hole [`Extent`]s of the file, in order.
+    ///
+    /// Useful for copying or scanning sparse files without reading through their holes.
+    /// Uses `SEEK_DATA`/`SEEK_HOLE`; where these are unavailable (including Windows), the
+    /// whole file is reported as a single [`ExtentKind::Data`] extent.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the size of the file cannot be determined.
+    /// Errors while seeking are returned by the iterator.
+    pub fn extents(&self) -> Result<Extents<'_>, HandleOpenError> {
+        let size = self.size()?;
+
+        #[cfg(unix)]
+        {
+            Ok(Extents::new(self.inner.fd(), size))
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            Ok(Extents::new(size))
+        }
+    }
+
+    /// Returns how the file is laid out on disk, using the `FIEMAP` ioctl.
+    ///
+    /// Holes are not reported, so the returned extents may have gaps between them.
+    ///
+    /// **Note:** Linux and Android only.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`HandleIoError::Unsupported`] if the filesystem does not support `FIEMAP`,
+    /// or another [`HandleIoError`] if the query fails.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn physical_extents(&self) -> Result<alloc::vec::Vec<PhysicalExtent>, HandleIoError> {
+        unix_common::fiemap(self.inner.fd())
+    }
+
+    /// Gives the operating system a hint about how a range of the file will be accessed,
+    /// e.g. to warm the page cache before mapping a region, or evict it after streaming through.
+    ///
+    /// Uses `posix_fadvise` where available. On Apple platforms only [`FileAdvice::WillNeed`]
+    /// has an effect, and on Windows the hint is ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The offset into the file where the range starts.
+    /// * `len` - The length of the range in bytes; 0 covers everything from `offset` onwards.
+    /// * `advice` - The expected access pattern.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleIoError`] if the hint is rejected, e.g. because the range is invalid.
+    pub fn advise(&self, offset: u64, len: u64, advice: FileAdvice) -> Result<(), HandleIoError> {
+        #[cfg(unix)]
+        {
+            unix_common::advise(self.inner.fd(), offset, len, advice)
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            let _ = (offset, len, advice);
+            Ok(())
+        }
+    }
+
+    /// Starts reading a range of the file into the page cache in the background.
+    ///
+    /// Uses `readahead` on Linux, and is equivalent to [`Self::advise`] with
+    /// [`FileAdvice::WillNeed`] on other Unix platforms. Ignored on Windows.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The offset into the file where the range starts.
+    /// * `len` - The length of the range in bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleIoError`] if the request is rejected.
+    pub fn readahead(&self, offset: u64, len: u64) -> Result<(), HandleIoError> {
+        #[cfg(unix)]
+        {
+            unix_common::readahead(self.inner.fd(), offset, len)
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            let _ = (offset, len);
+            Ok(())
+        }
+    }
+
+    /// Takes a shared lock on the whole file, blocking until it is available.
+    ///
+    /// Any number of shared locks can be held at once, but not alongside an exclusive lock.
+    /// Uses `flock` on Unix and `LockFileEx` on Windows. The lock is released when the
+    /// returned guard is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleIoError`] if locking fails.
+    pub fn lock_shared(&self) -> Result<FileLockGuard<'_>, HandleIoError> {
+        self.lock_with(None, LockKind::Shared, true)
+    }
+
+    /// Takes an exclusive lock on the whole file, blocking until it is available.
+    ///
+    /// See [`Self::lock_shared`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleIoError`] if locking fails.
+    pub fn lock_exclusive(&self) -> Result<FileLockGuard<'_>, HandleIoError> {
+        self.lock_with(None, LockKind::Exclusive, true)
+    }
+
+    /// Takes a shared lock on the whole file, if it is available right away.
+    ///
+    /// See [`Self::lock_shared`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`HandleIoError::WouldBlock`] if the file is locked exclusively elsewhere,
+    /// or another [`HandleIoError`] if locking fails.
+    pub fn try_lock_shared(&self) -> Result<FileLockGuard<'_>, HandleIoError> {
+        self.lock_with(None, LockKind::Shared, false)
+    }
+
+    /// Takes an exclusive lock on the whole file, if it is available right away.
+    ///
+    /// See [`Self::lock_shared`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`HandleIoError::WouldBlock`] if the file is locked elsewhere,
+    /// or another [`HandleIoError`] if locking fails.
+    pub fn try_lock_exclusive(&self) -> Result<FileLockGuard<'_>, HandleIoError> {
+        self.lock_with(None, LockKind::Exclusive, false)
+    }
+
+    /// Releases a lock on the whole file held through this handle.
+    ///
+    /// Dropping the [`FileLockGuard`] does this automatically; this is only needed
+    /// if the guard was leaked, e.g. with [`core::mem::forget`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleIoError`] if unlocking fails.
+    pub fn unlock(&self) -> Result<(), HandleIoError> {
+        #[cfg(unix)]
+        {
+            unix_common::unlock_file(self.inner.fd())
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            windows_common::lock_range(self.inner.handle(), 0, 0, None, false)
+        }
+    }
+
+    /// Takes a lock on a byte range of the file, blocking until it is available.
+    ///
+    /// Locks on ranges which do not overlap do not conflict, so several processes can
+    /// each own a region of the same file. A `len` of 0 covers everything from `offset`
+    /// onwards, however large the file grows.
+    ///
+    /// Uses open file description locks (`F_OFD_SETLKW`) on Linux and Android, which belong
+    /// to this handle. Elsewhere on Unix, classic `fcntl` locks are used instead; these belong
+    /// to the process, so they never conflict within a process, and closing *any* handle
+    /// to the file releases them. Range locks are separate from whole-file locks on Unix;
+    /// avoid mixing the two on one file.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The offset into the file where the range starts.
+    /// * `len` - The length of the range in bytes.
+    /// * `kind` - Whether to take a shared or an exclusive lock.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleIoError`] if locking fails, e.g. if `offset` or `len` exceeds
+    /// `i64::MAX` on Unix.
+    pub fn lock_range(
+        &self,
+        offset: u64,
+        len: u64,
+        kind: LockKind,
+    ) -> Result<FileLockGuard<'_>, HandleIoError> {
+        self.lock_with(Some((offset, len)), kind, true)
+    }
+
+    /// Takes a lock on a byte range of the file, if it is available right away.
+    ///
+    /// See [`Self::lock_range`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`HandleIoError::WouldBlock`] if an overlapping range is locked elsewhere,
+    /// or another [`HandleIoError`] if locking fails.
+    pub fn try_lock_range(
+        &self,
+        offset: u64,
+        len: u64,
+        kind: LockKind,
+    ) -> Result<FileLockGuard<'_>, HandleIoError> {
+        self.lock_with(Some((offset, len)), kind, false)
+    }
+
+    fn lock_with(
+        &self,
+        range: Option<(u64, u64)>,
+        kind: LockKind,
+        blocking: bool,
+    ) -> Result<FileLockGuard<'_>, HandleIoError> {
+        #[cfg(unix)]
+        {
+            FileLockGuard::acquire(self.inner.fd(), range, kind, blocking)
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            FileLockGuard::acquire(self.inner.handle(), range, kind, blocking)
+        }
+    }
+
+    /// Creates a new, independently owned handle to the same open file.
+    ///
+    /// Both handles share the underlying open file, so either can be used (or dropped)
+    /// without affecting the other.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the handle cannot be duplicated.
+    pub fn try_clone(&self) -> Result<Self, HandleOpenError> {
+        #[cfg(unix)]
+        {
+            let fd = unix_common::duplicate_fd(self.inner.fd())?;
+            Ok(unsafe { Self::from_fd(fd) })
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            let handle = windows_common::duplicate_handle(self.inner.handle(), None)?;
+            Ok(unsafe { Self::from_handle(handle) })
+        }
+    }
+
+    /// Creates a read-only handle to the same open file.
+    ///
+    /// This avoids reopening the file by path, so the new handle is guaranteed to refer
+    /// to the same file even if it has since been renamed or replaced.
+    ///
+    /// On Windows and Linux/Android, the new handle only has read access: Windows duplicates
+    /// the handle with `GENERIC_READ`, and Linux/Android reopen the file `O_RDONLY` through
+    /// `/proc/self/fd`. On other Unix platforms, or if `/proc` is unavailable, the descriptor
+    /// is duplicated as-is, so it keeps write access at the OS level, e.g. when written to
+    /// through `AsRawFd`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the handle cannot be duplicated.
+    pub fn to_read_only(&self) -> Result<ReadOnlyFileHandle, HandleOpenError> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        if let Ok(fd) = unix_common::reopen_read_only(self.inner.fd()) {
+            return Ok(unsafe { ReadOnlyFileHandle::from_fd(fd) });
+        }
+
+        #[cfg(unix)]
+        {
+            let fd = unix_common::duplicate_fd(self.inner.fd())?;
+            Ok(unsafe { ReadOnlyFileHandle::from_fd(fd) })
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            use windows_sys::Win32::Foundation::GENERIC_READ;
+            let handle = windows_common::duplicate_handle(self.inner.handle(), Some(GENERIC_READ))?;
+            Ok(unsafe { ReadOnlyFileHandle::from_handle(handle) })
+        }
+    }
+
+    /// Reads bytes from the file at the given offset into `buf`.
+    ///
+    /// This is intended for reading small regions (e.g. headers) where mapping the file
+    /// would be wasteful. On Unix, it does not use or update a file position; on Windows,
+    /// the file pointer is moved to the end of the read.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The offset into the file to start reading from.
+    /// * `buf` - The buffer to read into.
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes read, which may be less than `buf.len()`.
+    /// A value of 0 indicates the end of the file was reached.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleIoError`] if the read fails.
+    pub fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<usize, HandleIoError> {
+        #[cfg(unix)]
+        {
+            unix_common::read_at(self.inner.fd(), offset, buf)
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            windows_common::read_at(self.inner.handle(), offset, buf)
+        }
+    }
+
+    /// Reads exactly `buf.len()` bytes from the file at the given offset.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The offset into the file to start reading from.
+    /// * `buf` - The buffer to fill.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`HandleIoError::UnexpectedEof`] if the end of the file is reached before
+    /// the buffer is filled, or another [`HandleIoError`] if the read fails.
+    pub fn read_exact_at(&self, mut offset: u64, mut buf: &mut [u8]) -> Result<(), HandleIoError> {
+        while !buf.is_empty() {
+            match self.read_at(offset, buf)? {
+                0 => return Err(HandleIoError::UnexpectedEof),
+                read => {
+                    buf = &mut buf[read..];
+                    offset += read as u64;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes bytes from `buf` into the file at the given offset.
+    ///
+    /// Writing past the end of the file extends it. On Unix, this does not use or update
+    /// a file position; on Windows, the file pointer is moved to the end of the write.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The offset into the file to start writing at.
+    /// * `buf` - The data to write.
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes written, which may be less than `buf.len()`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleIoError`] if the write fails.
+    pub fn write_at(&self, offset: u64, buf: &[u8]) -> Result<usize, HandleIoError> {
+        #[cfg(unix)]
+        {
+            unix_common::write_at(self.inner.fd(), offset, buf)
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            windows_common::write_at(self.inner.handle(), offset, buf)
+        }
+    }
+
+    /// Writes the entirety of `buf` into the file at the given offset.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The offset into the file to start writing at.
+    /// * `buf` - The data to write.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`HandleIoError::WriteZero`] if the file stops accepting data before
+    /// the whole buffer is written, or another [`HandleIoError`] if the write fails.
+    pub fn write_all_at(&self, mut offset: u64, mut buf: &[u8]) -> Result<(), HandleIoError> {
+        while !buf.is_empty() {
+            match self.write_at(offset, buf)? {
+                0 => return Err(HandleIoError::WriteZero),
+                written => {
+                    buf = &buf[written..];
+                    offset += written as u64;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads from the file at the given offset into multiple buffers, filling them in order.
+    ///
+    /// Uses a single `preadv` call where available; otherwise falls back to one read per buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The offset into the file to start reading from.
+    /// * `bufs` - The buffers to read into.
+    ///
+    /// # Returns
+    ///
+    /// A [`VectoredTransfer`] describing how many bytes were read into each buffer.
+    /// Fewer bytes than the total buffer size may be read.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleIoError`] if the read fails.
+    #[cfg(feature = "std")]
+    pub fn read_vectored_at(
+        &self,
+        offset: u64,
+        bufs: &mut [std::io::IoSliceMut<'_>],
+    ) -> Result<VectoredTransfer, HandleIoError> {
+        #[cfg(unix)]
+        let total = unix_common::read_vectored_at(self.inner.fd(), offset, bufs)?;
+
+        #[cfg(target_os = "windows")]
+        let total = windows_common::read_vectored_at(self.inner.handle(), offset, bufs)?;
+
+        Ok(VectoredTransfer::new(total))
+    }
+
+    /// Writes multiple buffers, in order, into the file at the given offset.
+    ///
+    /// Uses a single `pwritev` call where available; otherwise falls back to one write per buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The offset into the file to start writing at.
+    /// * `bufs` - The buffers to write.
+    ///
+    /// # Returns
+    ///
+    /// A [`VectoredTransfer`] describing how many bytes were written from each buffer.
+    /// Fewer bytes than the total buffer size may be written.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleIoError`] if the write fails.
+    #[cfg(feature = "std")]
+    pub fn write_vectored_at(
+        &self,
+        offset: u64,
+        bufs: &[std::io::IoSlice<'_>],
+    ) -> Result<VectoredTransfer, HandleIoError> {
+        #[cfg(unix)]
+        let total = unix_common::write_vectored_at(self.inner.fd(), offset, bufs)?;
+
+        #[cfg(target_os = "windows")]
+        let total = windows_common::write_vectored_at(self.inner.handle(), offset, bufs)?;
+
+        Ok(VectoredTransfer::new(total))
+    }
+
+    /// Flushes all data and metadata of the file to the storage device, so that it
+    /// survives a crash or power loss.
+    ///
+    /// Uses `fsync` on Unix (`F_FULLFSYNC` on Apple platforms) and `FlushFileBuffers` on Windows.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleIoError::FailedToSync`] if the file cannot be flushed.
+    pub fn sync_all(&self) -> Result<(), HandleIoError> {
+        #[cfg(unix)]
+        {
+            unix_common::sync_all(self.inner.fd())
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            windows_common::sync_all(self.inner.handle())
+        }
+    }
+
+    /// Flushes the data of the file to the storage device, along with only the metadata
+    /// needed to read it back (such as the file size), skipping e.g. modification times.
+    ///
+    /// Uses `fdatasync` on Unix; the same as [`Self::sync_all`] on Apple platforms and Windows.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleIoError::FailedToSync`] if the file cannot be flushed.
+    pub fn sync_data(&self) -> Result<(), HandleIoError> {
+        #[cfg(unix)]
+        {
+            unix_common::sync_data(self.inner.fd())
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            windows_common::sync_all(self.inner.handle())
+        }
+    }
+
+    /// Writes back a range of the file, using `sync_file_range` on Linux.
+    ///
+    /// This is intended for controlling writeback of large files incrementally. It does not
+    /// flush metadata or the drive's cache, so it does **not** guarantee durability on its own;
+    /// follow up with [`Self::sync_data`] or [`Self::sync_all`] for that.
+    ///
+    /// On other platforms this falls back to [`Self::sync_data`] on the whole file (or
+    /// [`Self::sync_all`] on Windows), which is stronger than requested.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The offset into the file where the range starts.
+    /// * `len` - The length of the range, or 0 to include everything up to the end of the file.
+    /// * `flags` - Which parts of the writeback to perform. Does nothing if empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleIoError::FailedToSync`] if the range cannot be written back.
+    pub fn sync_range(
+        &self,
+        offset: u64,
+        len: u64,
+        flags: SyncRangeFlags,
+    ) -> Result<(), HandleIoError> {
+        #[cfg(unix)]
+        {
+            unix_common::sync_range(self.inner.fd(), offset, len, flags)
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            let _ = (offset, len);
+            if flags.is_empty() {
+                return Ok(());
+            }
+
+            windows_common::sync_all(self.inner.handle())
+        }
+    }
 }
 
 #[cfg(all(test, feature = "std"))]
@@ -309,4 +1030,284 @@ mod tests {
         assert_eq!(mapping.len(), expected_size.try_into().unwrap());
         assert!(!mapping.is_empty());
     }
+
+    #[test]
+    fn can_write_and_read_at_offset() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"Hello, World!").unwrap();
+        file.flush().unwrap();
+
+        let handle = ReadWriteFileHandle::open(file.path()).unwrap();
+        handle.write_all_at(7, b"WORLD").unwrap();
+
+        let mut buf = [0u8; 13];
+        handle.read_exact_at(0, &mut buf).unwrap();
+        assert_eq!(&buf, b"Hello, WORLD!");
+    }
+
+    #[test]
+    fn write_at_extends_file() {
+        let file = NamedTempFile::new().unwrap();
+        let handle = ReadWriteFileHandle::open(file.path()).unwrap();
+
+        handle.write_all_at(4, b"data").unwrap();
+        assert_eq!(handle.size().unwrap(), 8);
+
+        let mut buf = [0xFFu8; 8];
+        handle.read_exact_at(0, &mut buf).unwrap();
+        assert_eq!(&buf, b"\0\0\0\0data");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn can_write_and_read_past_4_gib() {
+        let file = NamedTempFile::new().unwrap();
+        let handle = ReadWriteFileHandle::open(file.path()).unwrap();
+
+        // Leaves a sparse hole, so the file takes no space for it.
+        let offset = (4 << 30) + 7;
+        match handle.write_all_at(offset, b"far away") {
+            Ok(()) => {}
+            // The temporary directory may be on a filesystem without large file support.
+            Err(HandleIoError::FailedToWrite(libc::EFBIG)) => return,
+            Err(e) => panic!("{e:?}"),
+        }
+        assert_eq!(handle.size().unwrap(), offset as i64 + 8);
+
+        let mut buf = [0u8; 8];
+        handle.read_exact_at(offset, &mut buf).unwrap();
+        assert_eq!(&buf, b"far away");
+        handle
+            .read_exact_at(offset & 0xFFFF_FFFF, &mut buf)
+            .unwrap();
+        assert_eq!(buf, [0; 8]);
+    }
+
+    #[test]
+    fn can_write_vectored_at_offset() {
+        use std::io::{IoSlice, IoSliceMut};
+
+        let file = NamedTempFile::new().unwrap();
+        let handle = ReadWriteFileHandle::open(file.path()).unwrap();
+
+        let bufs = [
+            IoSlice::new(b"Hello"),
+            IoSlice::new(b", "),
+            IoSlice::new(b"World!"),
+        ];
+        let transfer = handle.write_vectored_at(0, &bufs).unwrap();
+        assert_eq!(transfer.total(), 13);
+        assert_eq!(transfer.per_buffer(&bufs).collect::<Vec<_>>(), [5, 2, 6]);
+
+        let (mut first, mut second) = ([0u8; 7], [0u8; 6]);
+        let mut bufs = [IoSliceMut::new(&mut first), IoSliceMut::new(&mut second)];
+        assert_eq!(handle.read_vectored_at(0, &mut bufs).unwrap().total(), 13);
+        assert_eq!(&first, b"Hello, ");
+        assert_eq!(&second, b"World!");
+    }
+
+    #[test]
+    fn can_create_from_std_file() {
+        let file = NamedTempFile::new().unwrap();
+        let std_file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(file.path())
+            .unwrap();
+
+        let handle = ReadWriteFileHandle::from(std_file);
+        handle.write_all_at(0, b"Hello").unwrap();
+        assert_eq!(handle.size().unwrap(), 5);
+    }
+
+    #[test]
+    fn cloned_handle_writes_to_same_file() {
+        let file = NamedTempFile::new().unwrap();
+        let handle = ReadWriteFileHandle::open(file.path()).unwrap();
+        let clone = handle.try_clone().unwrap();
+
+        clone.write_all_at(0, b"Hello").unwrap();
+        drop(clone);
+        assert_eq!(handle.size().unwrap(), 5);
+    }
+
+    #[test]
+    fn read_only_handle_sees_writes_after_rename() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("original.bin");
+        let handle = ReadWriteFileHandle::create_preallocated(&path, 0).unwrap();
+        handle.write_all_at(0, b"Hello, World!").unwrap();
+
+        // Downgrade must refer to the open file, not whatever is at the path now.
+        rename(&path, dir.path().join("renamed.bin")).unwrap();
+        File::create(&path).unwrap();
+
+        let read_only = handle.to_read_only().unwrap();
+        drop(handle);
+
+        let mut buf = [0u8; 13];
+        read_only.read_exact_at(0, &mut buf).unwrap();
+        assert_eq!(&buf, b"Hello, World!");
+    }
+
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn read_only_handle_cannot_write() {
+        use std::os::fd::AsRawFd;
+
+        let file = NamedTempFile::new().unwrap();
+        let handle = ReadWriteFileHandle::open(file.path()).unwrap();
+        let read_only = handle.to_read_only().unwrap();
+
+        let flags = unsafe { libc::fcntl(read_only.as_raw_fd(), libc::F_GETFL) };
+        assert_eq!(flags & libc::O_ACCMODE, libc::O_RDONLY);
+        let written = unsafe { libc::write(read_only.as_raw_fd(), b"x".as_ptr().cast(), 1) };
+        assert_eq!(written, -1);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn can_open_non_utf8_path() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(OsStr::from_bytes(b"caf\xe9.bin"));
+
+        let handle = ReadWriteFileHandle::create_preallocated(&path, 0).unwrap();
+        handle.write_all_at(0, b"Hello").unwrap();
+        drop(handle);
+
+        let handle = ReadOnlyFileHandle::open(&path).unwrap();
+        assert_eq!(handle.size().unwrap(), 5);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn can_open_from_cstr() {
+        use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("cstr.bin");
+        let c_path = CString::new(path.as_os_str().as_bytes()).unwrap();
+
+        let handle = ReadWriteFileHandle::create_preallocated_cstr(&c_path, 13).unwrap();
+        handle.write_all_at(0, b"Hello, World!").unwrap();
+        drop(handle);
+
+        let handle = ReadOnlyFileHandle::open_cstr(&c_path).unwrap();
+        assert_eq!(handle.size().unwrap(), 13);
+        assert!(ReadWriteFileHandle::open_cstr(c"/nonexistent/cstr.bin").is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn temp_file_is_not_visible_in_directory() {
+        let dir = TempDir::new().unwrap();
+        let handle = ReadWriteFileHandle::create_temp_in(dir.path(), 1024).unwrap();
+
+        handle.write_all_at(0, b"Hello").unwrap();
+        assert_eq!(handle.size().unwrap(), 1024);
+        assert_eq!(read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn temp_file_can_be_persisted() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("persisted.bin");
+
+        let handle = ReadWriteFileHandle::create_temp_in(dir.path(), 5).unwrap();
+        handle.write_all_at(0, b"Hello").unwrap();
+        match handle.persist(&path) {
+            Ok(()) => {}
+            // O_TMPFILE is not supported here, and the deleted mkstemp fallback cannot be linked.
+            Err(HandleIoError::FailedToPersist(libc::ENOENT)) => return,
+            Err(e) => panic!("{e:?}"),
+        }
+        assert_eq!(read(&path).unwrap(), b"Hello");
+        assert!(handle.persist(&path).is_err());
+    }
+
+    #[test]
+    fn can_sync_written_data() {
+        let file = NamedTempFile::new().unwrap();
+        let handle = ReadWriteFileHandle::open(file.path()).unwrap();
+        handle.write_all_at(0, b"Hello, World!").unwrap();
+
+        handle.sync_all().unwrap();
+        handle.sync_data().unwrap();
+        handle.sync_range(0, 13, SyncRangeFlags::all()).unwrap();
+        handle.sync_range(0, 0, SyncRangeFlags::empty()).unwrap();
+    }
+
+    #[test]
+    fn set_len_grows_and_shrinks_keeping_content() {
+        let file = NamedTempFile::new().unwrap();
+        let handle = ReadWriteFileHandle::open(file.path()).unwrap();
+        handle.write_all_at(0, b"Hello, World!").unwrap();
+
+        handle.set_len(8192).unwrap();
+        assert_eq!(handle.size().unwrap(), 8192);
+
+        handle.set_len(5).unwrap();
+        assert_eq!(handle.size().unwrap(), 5);
+
+        let mut buf = [0u8; 5];
+        handle.read_exact_at(0, &mut buf).unwrap();
+        assert_eq!(&buf, b"Hello");
+    }
+
+    #[test]
+    fn allocate_only_grows() {
+        let file = NamedTempFile::new().unwrap();
+        let handle = ReadWriteFileHandle::open(file.path()).unwrap();
+        handle.write_all_at(0, b"Hello, World!").unwrap();
+
+        handle.allocate(4).unwrap();
+        assert_eq!(handle.size().unwrap(), 13);
+
+        handle.allocate(4096).unwrap();
+        assert_eq!(handle.size().unwrap(), 4096);
+
+        let mut buf = [0u8; 13];
+        handle.read_exact_at(0, &mut buf).unwrap();
+        assert_eq!(&buf, b"Hello, World!");
+    }
+
+    #[test]
+    fn punch_hole_and_zero_range_zero_data_in_place() {
+        let file = NamedTempFile::new().unwrap();
+        let handle = ReadWriteFileHandle::open(file.path()).unwrap();
+        handle.write_all_at(0, &[0xAA; 16384]).unwrap();
+
+        let (punched, zeroed) = match (handle.punch_hole(4096, 4096), handle.zero_range(12288, 10))
+        {
+            (Err(HandleIoError::Unsupported), _) | (_, Err(HandleIoError::Unsupported)) => return,
+            results => results,
+        };
+        punched.unwrap();
+        zeroed.unwrap();
+
+        let mut data = [0u8; 16384];
+        handle.read_exact_at(0, &mut data).unwrap();
+        assert_eq!(handle.size().unwrap(), 16384);
+        assert!(data[..4096].iter().all(|&x| x == 0xAA));
+        assert!(data[4096..8192].iter().all(|&x| x == 0));
+        assert!(data[8192..12288].iter().all(|&x| x == 0xAA));
+        assert!(data[12288..12298].iter().all(|&x| x == 0));
+        assert!(data[12298..].iter().all(|&x| x == 0xAA));
+    }
+
+    #[test]
+    fn preallocate_keep_size_does_not_change_size() {
+        let file = NamedTempFile::new().unwrap();
+        let handle = ReadWriteFileHandle::open(file.path()).unwrap();
+        handle.write_all_at(0, b"Hello").unwrap();
+
+        match handle.preallocate_keep_size(0, 65536) {
+            Ok(()) | Err(HandleIoError::Unsupported) => {}
+            Err(e) => panic!("{e:?}"),
+        }
+        assert_eq!(handle.size().unwrap(), 5);
+    }
 }
@@ -1,5 +1,8 @@
 use super::*;
+use core::ffi::CStr;
 use libc::*;
+#[cfg(feature = "std")]
+use std::os::unix::ffi::OsStrExt;
 use unix_common::*;
 
 /// Unix platform-specific implementation for [`ReadWriteFileHandle`].
@@ -13,30 +16,140 @@ unsafe impl Send for InnerHandle {}
 impl InnerHandle {
     #[cfg(feature = "std")]
     pub fn open(path: &std::path::Path) -> Result<Self, HandleOpenError> {
-        let path_str = path.to_str().ok_or_else(|| {
-            HandleOpenError::failed_to_open_file_handle_unix(-1, "<invalid_utf8>")
-        })?;
-        let fd = open_with_flags(path_str, O_RDWR)?;
+        let fd = open_with_flags(path.as_os_str().as_bytes(), O_RDWR, DEFAULT_FILE_MODE)?;
         Ok(InnerHandle { fd })
     }
 
     #[cfg(not(feature = "std"))]
     pub fn open(path: &str) -> Result<Self, HandleOpenError> {
-        let fd = open_with_flags(path, O_RDWR)?;
+        let fd = open_with_flags(path.as_bytes(), O_RDWR, DEFAULT_FILE_MODE)?;
         Ok(InnerHandle { fd })
     }
 
+    #[cfg(feature = "std")]
+    pub fn open_with_options(
+        path: &std::path::Path,
+        options: &FileHandleOptions,
+    ) -> Result<Self, HandleOpenError> {
+        let fd = open_with_flags(
+            path.as_os_str().as_bytes(),
+            options.unix_flags(O_RDWR),
+            options.file_mode(),
+        )?;
+        Ok(InnerHandle { fd })
+    }
+
+    #[cfg(not(feature = "std"))]
+    pub fn open_with_options(
+        path: &str,
+        options: &FileHandleOptions,
+    ) -> Result<Self, HandleOpenError> {
+        let fd = open_with_flags(
+            path.as_bytes(),
+            options.unix_flags(O_RDWR),
+            options.file_mode(),
+        )?;
+        Ok(InnerHandle { fd })
+    }
+
+    /// Opens the file with read-write access, from a path given as raw bytes.
+    #[cfg(not(feature = "std"))]
+    pub fn open_bytes(path: &[u8]) -> Result<Self, HandleOpenError> {
+        let fd = open_with_flags(path, O_RDWR, DEFAULT_FILE_MODE)?;
+        Ok(InnerHandle { fd })
+    }
+
+    /// Creates a file with pre-allocated size, from a path given as raw bytes.
+    #[cfg(not(feature = "std"))]
+    pub fn create_preallocated_bytes(path: &[u8], size: i64) -> Result<Self, HandleOpenError> {
+        let fd = open_with_flags(path, O_RDWR | O_CREAT, DEFAULT_FILE_MODE)?;
+        if let Err(e) = set_file_size(fd, size) {
+            unsafe { close(fd) };
+            return Err(e);
+        }
+
+        Ok(InnerHandle { fd })
+    }
+
+    /// Opens the file with read-write access, from a NUL-terminated path, without allocating.
+    pub fn open_cstr(path: &CStr) -> Result<Self, HandleOpenError> {
+        let fd = open_cstr_with_flags(path, O_RDWR, DEFAULT_FILE_MODE)?;
+        Ok(InnerHandle { fd })
+    }
+
+    /// Creates a file with pre-allocated size, from a NUL-terminated path, without allocating.
+    pub fn create_preallocated_cstr(path: &CStr, size: i64) -> Result<Self, HandleOpenError> {
+        let fd = open_cstr_with_flags(path, O_RDWR | O_CREAT, DEFAULT_FILE_MODE)?;
+        if let Err(e) = set_file_size(fd, size) {
+            unsafe { close(fd) };
+            return Err(e);
+        }
+
+        Ok(InnerHandle { fd })
+    }
+
+    /// Opens the file with read-write access relative to an open directory,
+    /// using the given [`FileHandleOptions`].
+    pub fn open_at(
+        dir_fd: c_int,
+        path: &[u8],
+        options: &FileHandleOptions,
+    ) -> Result<Self, HandleOpenError> {
+        let open = if options.resolves_beneath() {
+            openat_beneath_with_flags
+        } else {
+            openat_with_flags
+        };
+        let fd = open(
+            dir_fd,
+            path,
+            options.unix_flags(O_RDWR),
+            options.file_mode(),
+        )?;
+        Ok(InnerHandle { fd })
+    }
+
+    /// Creates a file with pre-allocated size relative to an open directory,
+    /// using the given [`FileHandleOptions`].
+    pub fn create_preallocated_at(
+        dir_fd: c_int,
+        path: &[u8],
+        size: i64,
+        options: &FileHandleOptions,
+    ) -> Result<Self, HandleOpenError> {
+        let handle = Self::open_at(dir_fd, path, &options.create(true))?;
+        set_file_size(handle.fd, size)?;
+        Ok(handle)
+    }
+
     /// Returns the raw file descriptor.
     pub fn fd(&self) -> c_int {
         self.fd
     }
 
+    /// Creates the handle from an existing file descriptor, taking ownership of it.
+    ///
+    /// # Safety
+    ///
+    /// `fd` must be an open file descriptor which is not owned (closed) by anything else.
+    pub unsafe fn from_raw_fd(fd: c_int) -> Self {
+        InnerHandle { fd }
+    }
+
+    /// Consumes the handle, returning the file descriptor without closing it.
+    pub fn into_raw_fd(self) -> c_int {
+        let fd = self.fd;
+        core::mem::forget(self);
+        fd
+    }
+
     #[cfg(feature = "std")]
     pub fn create_preallocated(path: &std::path::Path, size: i64) -> Result<Self, HandleOpenError> {
-        let path_str = path.to_str().ok_or_else(|| {
-            HandleOpenError::failed_to_open_file_handle_unix(-1, "<invalid_utf8>")
-        })?;
-        let fd = open_with_flags(path_str, O_RDWR | O_CREAT)?;
+        let fd = open_with_flags(
+            path.as_os_str().as_bytes(),
+            O_RDWR | O_CREAT,
+            DEFAULT_FILE_MODE,
+        )?;
         if let Err(e) = set_file_size(fd, size) {
             unsafe { close(fd) };
             return Err(e);
@@ -47,7 +160,7 @@ impl InnerHandle {
 
     #[cfg(not(feature = "std"))]
     pub fn create_preallocated(path: &str, size: i64) -> Result<Self, HandleOpenError> {
-        let fd = open_with_flags(path, O_RDWR | O_CREAT)?;
+        let fd = open_with_flags(path.as_bytes(), O_RDWR | O_CREAT, DEFAULT_FILE_MODE)?;
         if let Err(e) = set_file_size(fd, size) {
             unsafe { close(fd) };
             return Err(e);
@@ -64,3 +177,413 @@ impl Drop for InnerHandle {
         }
     }
 }
+
+impl ReadWriteFileHandle {
+    /// Opens a file in read-write mode with shared access, relative to an open directory.
+    ///
+    /// The path is resolved from `dir` itself (`openat`), not from the directory's path.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory `path` is relative to.
+    /// * `path` - The path to the file to open, relative to `dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the file cannot be opened.
+    #[cfg(feature = "std")]
+    pub fn open_at<P: AsRef<std::path::Path>>(
+        dir: &DirHandle,
+        path: P,
+    ) -> Result<Self, HandleOpenError> {
+        let inner = InnerHandle::open_at(
+            dir.fd(),
+            path.as_ref().as_os_str().as_bytes(),
+            &FileHandleOptions::new(),
+        )?;
+        Ok(ReadWriteFileHandle { inner })
+    }
+
+    /// Opens a file in read-write mode with shared access, relative to an open directory.
+    ///
+    /// The path is resolved from `dir` itself (`openat`), not from the directory's path.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory `path` is relative to.
+    /// * `path` - The path to the file to open, relative to `dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the file cannot be opened.
+    #[cfg(not(feature = "std"))]
+    pub fn open_at(dir: &DirHandle, path: &str) -> Result<Self, HandleOpenError> {
+        let inner = InnerHandle::open_at(dir.fd(), path.as_bytes(), &FileHandleOptions::new())?;
+        Ok(ReadWriteFileHandle { inner })
+    }
+
+    /// Creates a new file with pre-allocated size, relative to an open directory.
+    ///
+    /// See [`Self::create_preallocated`] for details.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory `path` is relative to.
+    /// * `path` - The path where the file should be created, relative to `dir`.
+    /// * `size` - The size to pre-allocate in bytes
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the file cannot be created or pre-allocation fails.
+    #[cfg(feature = "std")]
+    pub fn create_preallocated_at<P: AsRef<std::path::Path>>(
+        dir: &DirHandle,
+        path: P,
+        size: i64,
+    ) -> Result<Self, HandleOpenError> {
+        let inner = InnerHandle::create_preallocated_at(
+            dir.fd(),
+            path.as_ref().as_os_str().as_bytes(),
+            size,
+            &FileHandleOptions::new(),
+        )?;
+        Ok(ReadWriteFileHandle { inner })
+    }
+
+    /// Creates a new file with pre-allocated size, relative to an open directory.
+    ///
+    /// See [`Self::create_preallocated`] for details.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory `path` is relative to.
+    /// * `path` - The path where the file should be created, relative to `dir`.
+    /// * `size` - The size to pre-allocate in bytes
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the file cannot be created or pre-allocation fails.
+    #[cfg(not(feature = "std"))]
+    pub fn create_preallocated_at(
+        dir: &DirHandle,
+        path: &str,
+        size: i64,
+    ) -> Result<Self, HandleOpenError> {
+        let inner = InnerHandle::create_preallocated_at(
+            dir.fd(),
+            path.as_bytes(),
+            size,
+            &FileHandleOptions::new(),
+        )?;
+        Ok(ReadWriteFileHandle { inner })
+    }
+
+    /// Creates a new file with pre-allocated size, refusing to resolve outside of `dir`.
+    ///
+    /// See [`Self::create_preallocated`] and [`FileHandleOptions::resolve_beneath`] for details.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The root directory `path` must stay beneath.
+    /// * `path` - The path where the file should be created, relative to `dir`.
+    /// * `size` - The size to pre-allocate in bytes
+    ///
+    /// # Errors
+    ///
+    /// Returns [`HandleOpenError::PathNotBeneathRoot`] if the path would escape `dir`,
+    /// or another [`HandleOpenError`] if the file cannot be created or pre-allocation fails.
+    #[cfg(feature = "std")]
+    pub fn create_preallocated_beneath<P: AsRef<std::path::Path>>(
+        dir: &DirHandle,
+        path: P,
+        size: i64,
+    ) -> Result<Self, HandleOpenError> {
+        let options = FileHandleOptions::new().resolve_beneath(true);
+        let inner = InnerHandle::create_preallocated_at(
+            dir.fd(),
+            path.as_ref().as_os_str().as_bytes(),
+            size,
+            &options,
+        )?;
+        Ok(ReadWriteFileHandle { inner })
+    }
+
+    /// Creates a new file with pre-allocated size, refusing to resolve outside of `dir`.
+    ///
+    /// See [`Self::create_preallocated`] and [`FileHandleOptions::resolve_beneath`] for details.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The root directory `path` must stay beneath.
+    /// * `path` - The path where the file should be created, relative to `dir`.
+    /// * `size` - The size to pre-allocate in bytes
+    ///
+    /// # Errors
+    ///
+    /// Returns [`HandleOpenError::PathNotBeneathRoot`] if the path would escape `dir`,
+    /// or another [`HandleOpenError`] if the file cannot be created or pre-allocation fails.
+    #[cfg(not(feature = "std"))]
+    pub fn create_preallocated_beneath(
+        dir: &DirHandle,
+        path: &str,
+        size: i64,
+    ) -> Result<Self, HandleOpenError> {
+        let options = FileHandleOptions::new().resolve_beneath(true);
+        let inner = InnerHandle::create_preallocated_at(dir.fd(), path.as_bytes(), size, &options)?;
+        Ok(ReadWriteFileHandle { inner })
+    }
+
+    /// Creates an unnamed temporary file with pre-allocated size in the directory `dir`.
+    ///
+    /// The file is not visible in the file system, and is removed once all handles to it
+    /// are closed (including if the process crashes). On Linux, it is created with `O_TMPFILE`
+    /// and can be given a name with [`Self::persist`] once it is complete.
+    ///
+    /// If `O_TMPFILE` is unsupported (non-Linux systems, some filesystems), the file is created
+    /// with `mkstemp` and unlinked immediately instead; such files cannot be persisted.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory to create the file in. To persist the file, this must be
+    ///   on the same filesystem as the final path.
+    /// * `size` - The size to pre-allocate in bytes
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the file cannot be created or pre-allocation fails.
+    #[cfg(feature = "std")]
+    pub fn create_temp_in<P: AsRef<std::path::Path>>(
+        dir: P,
+        size: i64,
+    ) -> Result<Self, HandleOpenError> {
+        let fd = create_temp_in(dir.as_ref().as_os_str().as_bytes())?;
+        let handle = unsafe { Self::from_fd(fd) };
+        set_file_size(fd, size)?;
+        Ok(handle)
+    }
+
+    /// Creates an unnamed temporary file with pre-allocated size in the directory `dir`.
+    ///
+    /// The file is not visible in the file system, and is removed once all handles to it
+    /// are closed (including if the process crashes). On Linux, it is created with `O_TMPFILE`
+    /// and can be given a name with [`Self::persist`] once it is complete.
+    ///
+    /// If `O_TMPFILE` is unsupported (non-Linux systems, some filesystems), the file is created
+    /// with `mkstemp` and unlinked immediately instead; such files cannot be persisted.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory to create the file in. To persist the file, this must be
+    ///   on the same filesystem as the final path.
+    /// * `size` - The size to pre-allocate in bytes
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the file cannot be created or pre-allocation fails.
+    #[cfg(not(feature = "std"))]
+    pub fn create_temp_in(dir: &str, size: i64) -> Result<Self, HandleOpenError> {
+        let fd = create_temp_in(dir.as_bytes())?;
+        let handle = unsafe { Self::from_fd(fd) };
+        set_file_size(fd, size)?;
+        Ok(handle)
+    }
+
+    /// Links a temporary file created by [`Self::create_temp_in`] into the file system at `path`,
+    /// making it visible atomically with all of its contents.
+    ///
+    /// The handle remains open and usable afterwards. Linux and Android only.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to give the file. Must not already exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleIoError::FailedToPersist`] if the file cannot be linked, for example
+    /// because `path` already exists or the file was created by the `mkstemp` fallback.
+    #[cfg(all(any(target_os = "linux", target_os = "android"), feature = "std"))]
+    pub fn persist<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), HandleIoError> {
+        unix_common::persist(self.inner.fd(), path.as_ref().as_os_str().as_bytes())
+    }
+
+    /// Links a temporary file created by [`Self::create_temp_in`] into the file system at `path`,
+    /// making it visible atomically with all of its contents.
+    ///
+    /// The handle remains open and usable afterwards. Linux and Android only.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to give the file. Must not already exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleIoError::FailedToPersist`] if the file cannot be linked, for example
+    /// because `path` already exists or the file was created by the `mkstemp` fallback.
+    #[cfg(all(any(target_os = "linux", target_os = "android"), not(feature = "std")))]
+    pub fn persist(&self, path: &str) -> Result<(), HandleIoError> {
+        unix_common::persist(self.inner.fd(), path.as_bytes())
+    }
+
+    /// Opens a file relative to an open directory, using the given [`FileHandleOptions`].
+    ///
+    /// Use [`FileHandleOptions::open_read_write_at`] instead.
+    pub(crate) fn open_at_with_options(
+        dir: &DirHandle,
+        path: &[u8],
+        options: &FileHandleOptions,
+    ) -> Result<Self, HandleOpenError> {
+        let inner = InnerHandle::open_at(dir.fd(), path, options)?;
+        Ok(ReadWriteFileHandle { inner })
+    }
+
+    /// Opens a file in read-write mode with shared access, from a NUL-terminated path.
+    ///
+    /// The path is passed straight to `open`, so no allocation takes place on success.
+    /// Useful in `no_std` environments which already hold NUL-terminated paths.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the file to open.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the file cannot be opened.
+    pub fn open_cstr(path: &CStr) -> Result<Self, HandleOpenError> {
+        let inner = InnerHandle::open_cstr(path)?;
+        Ok(ReadWriteFileHandle { inner })
+    }
+
+    /// Creates a new file with pre-allocated size, from a NUL-terminated path.
+    ///
+    /// See [`Self::create_preallocated`] for details.
+    /// The path is passed straight to `open`, so no allocation takes place on success.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path where the file should be created
+    /// * `size` - The size to pre-allocate in bytes
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the file cannot be created or pre-allocation fails.
+    pub fn create_preallocated_cstr(path: &CStr, size: i64) -> Result<Self, HandleOpenError> {
+        let inner = InnerHandle::create_preallocated_cstr(path, size)?;
+        Ok(ReadWriteFileHandle { inner })
+    }
+
+    /// Creates a handle which takes ownership of the given file descriptor.
+    ///
+    /// # Safety
+    ///
+    /// See [`InnerHandle::from_raw_fd`].
+    pub(crate) unsafe fn from_fd(fd: c_int) -> Self {
+        ReadWriteFileHandle {
+            inner: InnerHandle::from_raw_fd(fd),
+        }
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl ReadWriteFileHandle {
+    /// Opens a file in read-write mode with shared access, from a path given as raw bytes.
+    ///
+    /// Unix paths are arbitrary bytes, so this can open files whose names are not valid UTF-8.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the file to open.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the file cannot be opened.
+    pub fn open_bytes(path: &[u8]) -> Result<Self, HandleOpenError> {
+        let inner = InnerHandle::open_bytes(path)?;
+        Ok(ReadWriteFileHandle { inner })
+    }
+
+    /// Creates a new file with pre-allocated size, from a path given as raw bytes.
+    ///
+    /// See [`Self::create_preallocated`] for details.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path where the file should be created
+    /// * `size` - The size to pre-allocate in bytes
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HandleOpenError`] if the file cannot be created or pre-allocation fails.
+    pub fn create_preallocated_bytes(path: &[u8], size: i64) -> Result<Self, HandleOpenError> {
+        let inner = InnerHandle::create_preallocated_bytes(path, size)?;
+        Ok(ReadWriteFileHandle { inner })
+    }
+
+    /// Creates a handle from an existing file descriptor, taking ownership of it.
+    ///
+    /// # Safety
+    ///
+    /// `fd` must be an open file descriptor with read-write access, which is not
+    /// owned (closed) by anything else.
+    pub unsafe fn from_raw_fd(fd: c_int) -> Self {
+        Self::from_fd(fd)
+    }
+
+    /// Consumes the handle, returning the file descriptor without closing it.
+    pub fn into_raw_fd(self) -> c_int {
+        self.inner.into_raw_fd()
+    }
+}
+
+#[cfg(feature = "std")]
+mod std_impls {
+    use super::*;
+    use std::fs::File;
+    use std::os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, OwnedFd, RawFd};
+
+    impl AsRawFd for ReadWriteFileHandle {
+        fn as_raw_fd(&self) -> RawFd {
+            self.inner.fd()
+        }
+    }
+
+    impl AsFd for ReadWriteFileHandle {
+        fn as_fd(&self) -> BorrowedFd<'_> {
+            unsafe { BorrowedFd::borrow_raw(self.inner.fd()) }
+        }
+    }
+
+    impl FromRawFd for ReadWriteFileHandle {
+        /// Creates a handle from an existing file descriptor, taking ownership of it.
+        ///
+        /// The descriptor must have been opened with read-write access.
+        unsafe fn from_raw_fd(fd: RawFd) -> Self {
+            Self::from_fd(fd)
+        }
+    }
+
+    impl IntoRawFd for ReadWriteFileHandle {
+        fn into_raw_fd(self) -> RawFd {
+            self.inner.into_raw_fd()
+        }
+    }
+
+    impl From<OwnedFd> for ReadWriteFileHandle {
+        /// Takes ownership of a file descriptor opened with read-write access.
+        fn from(fd: OwnedFd) -> Self {
+            unsafe { Self::from_raw_fd(fd.into_raw_fd()) }
+        }
+    }
+
+    impl From<File> for ReadWriteFileHandle {
+        /// Takes ownership of a [`File`] opened with read-write access.
+        fn from(file: File) -> Self {
+            OwnedFd::from(file).into()
+        }
+    }
+
+    impl From<ReadWriteFileHandle> for OwnedFd {
+        fn from(handle: ReadWriteFileHandle) -> Self {
+            unsafe { OwnedFd::from_raw_fd(handle.into_raw_fd()) }
+        }
+    }
+}
@@ -1,44 +1,524 @@
-use super::HandleOpenError;
-use alloc::ffi::CString;
+use super::{HandleIoError, HandleOpenError};
+use alloc::{ffi::CString, vec::Vec};
+use core::ffi::CStr;
 use core::mem::zeroed;
 use libc::*;
 
-/// Default file permissions: rw-r--r-- (644)
-const DEFAULT_FILE_MODE: mode_t = S_IRUSR | S_IWUSR | S_IRGRP | S_IROTH;
-
 /// Opens the file with specified access mode.
 ///
 /// # Arguments
 ///
+/// * `path` - The path to the file to open, as raw bytes (need not be UTF-8).
+/// * `flags` - Open flags (O_RDONLY or O_RDWR, plus any extra flags)
+/// * `mode` - Permission bits used if the file is created (e.g. [`super::DEFAULT_FILE_MODE`])
+///
+/// # Errors
+///
+/// Returns a `HandleOpenError` if the file cannot be opened.
+pub(crate) fn open_with_flags(
+    path: &[u8],
+    flags: c_int,
+    mode: u32,
+) -> Result<c_int, HandleOpenError> {
+    openat_with_flags(AT_FDCWD, path, flags, mode)
+}
+
+/// Opens the file with specified access mode, from an already NUL-terminated path.
+///
+/// Unlike [`open_with_flags`], this does not allocate; the pointer is passed straight to `open`.
+///
+/// # Arguments
+///
 /// * `path` - The path to the file to open.
-/// * `flags` - Open flags (O_RDONLY or O_RDWR)
+/// * `flags` - Open flags (O_RDONLY or O_RDWR, plus any extra flags)
+/// * `mode` - Permission bits used if the file is created (e.g. [`super::DEFAULT_FILE_MODE`])
 ///
 /// # Errors
 ///
 /// Returns a `HandleOpenError` if the file cannot be opened.
-pub(crate) fn open_with_flags(path: &str, flags: c_int) -> Result<c_int, HandleOpenError> {
+pub(crate) fn open_cstr_with_flags(
+    path: &CStr,
+    flags: c_int,
+    mode: u32,
+) -> Result<c_int, HandleOpenError> {
+    openat_cstr_with_flags(AT_FDCWD, path, flags, mode)
+}
+
+/// Opens the file with specified access mode, relative to an open directory.
+///
+/// # Arguments
+///
+/// * `dir_fd` - Descriptor of the directory `path` is relative to, or `AT_FDCWD`.
+/// * `path` - The path to the file to open, as raw bytes (need not be UTF-8).
+/// * `flags` - Open flags (O_RDONLY or O_RDWR, plus any extra flags)
+/// * `mode` - Permission bits used if the file is created (e.g. [`super::DEFAULT_FILE_MODE`])
+///
+/// # Errors
+///
+/// Returns a `HandleOpenError` if the file cannot be opened.
+pub(crate) fn openat_with_flags(
+    dir_fd: c_int,
+    path: &[u8],
+    flags: c_int,
+    mode: u32,
+) -> Result<c_int, HandleOpenError> {
     let c_path = CString::new(path)
-        .map_err(|_| HandleOpenError::failed_to_open_file_handle_unix(-1, path))?;
+        .map_err(|_| HandleOpenError::failed_to_open_file_handle_unix_bytes(-1, path))?;
+
+    openat_cstr_with_flags(dir_fd, &c_path, flags, mode)
+}
 
+/// Opens the file with specified access mode, relative to an open directory,
+/// from an already NUL-terminated path.
+///
+/// # Arguments
+///
+/// * `dir_fd` - Descriptor of the directory `path` is relative to, or `AT_FDCWD`.
+/// * `path` - The path to the file to open.
+/// * `flags` - Open flags (O_RDONLY or O_RDWR, plus any extra flags)
+/// * `mode` - Permission bits used if the file is created (e.g. [`super::DEFAULT_FILE_MODE`])
+///
+/// # Errors
+///
+/// Returns a `HandleOpenError` if the file cannot be opened.
+pub(crate) fn openat_cstr_with_flags(
+    dir_fd: c_int,
+    path: &CStr,
+    flags: c_int,
+    mode: u32,
+) -> Result<c_int, HandleOpenError> {
     // If O_CREAT is in flags, provide mode, otherwise mode is ignored
     let fd = unsafe {
         if flags & O_CREAT != 0 {
-            open(c_path.as_ptr(), flags, DEFAULT_FILE_MODE as c_uint)
+            openat(dir_fd, path.as_ptr(), flags, mode as c_uint)
         } else {
-            open(c_path.as_ptr(), flags)
+            openat(dir_fd, path.as_ptr(), flags)
         }
     };
 
     if fd < 0 {
-        return Err(HandleOpenError::failed_to_open_file_handle_unix(
+        return Err(HandleOpenError::failed_to_open_file_handle_unix_bytes(
             errno::errno().0,
-            path,
+            path.to_bytes(),
         ));
     }
 
     Ok(fd)
 }
 
+/// Opens the file relative to an open directory, refusing to resolve to anything outside of it.
+///
+/// Absolute paths, `..` components and symbolic links (in any component) are rejected with
+/// [`HandleOpenError::PathNotBeneathRoot`]. On Linux 5.6+ this uses `openat2` with
+/// `RESOLVE_BENEATH | RESOLVE_NO_SYMLINKS`; elsewhere, or if `openat2` is unavailable
+/// (`ENOSYS`, or `EPERM` under some seccomp filters), the path is walked one component
+/// at a time with `O_NOFOLLOW`.
+///
+/// # Arguments
+///
+/// * `dir_fd` - Descriptor of the root directory `path` is relative to.
+/// * `path` - The path to the file to open, as raw bytes (need not be UTF-8).
+/// * `flags` - Open flags (O_RDONLY or O_RDWR, plus any extra flags)
+/// * `mode` - Permission bits used if the file is created (e.g. [`super::DEFAULT_FILE_MODE`])
+///
+/// # Errors
+///
+/// Returns a `HandleOpenError` if the file cannot be opened.
+pub(crate) fn openat_beneath_with_flags(
+    dir_fd: c_int,
+    path: &[u8],
+    flags: c_int,
+    mode: u32,
+) -> Result<c_int, HandleOpenError> {
+    // Rejected up front, so behaviour does not depend on which strategy is used below.
+    if path.first() == Some(&b'/') || path.split(|&c| c == b'/').any(|c| c == b"..") {
+        return Err(HandleOpenError::path_not_beneath_root(path));
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let c_path = CString::new(path)
+            .map_err(|_| HandleOpenError::failed_to_open_file_handle_unix_bytes(-1, path))?;
+
+        let mut how: open_how = unsafe { zeroed() };
+        how.flags = flags as u64;
+        if flags & O_CREAT != 0 {
+            how.mode = mode as u64;
+        }
+        how.resolve = RESOLVE_BENEATH | RESOLVE_NO_SYMLINKS;
+
+        let fd = unsafe {
+            syscall(
+                SYS_openat2,
+                dir_fd,
+                c_path.as_ptr(),
+                &how as *const open_how,
+                core::mem::size_of::<open_how>(),
+            )
+        };
+
+        if fd >= 0 {
+            return Ok(fd as c_int);
+        }
+
+        match errno::errno().0 {
+            ENOSYS | EPERM => {}
+            code => return Err(beneath_error(code, path)),
+        }
+    }
+
+    openat_beneath_walk(dir_fd, path, flags, mode)
+}
+
+/// Fallback for [`openat_beneath_with_flags`], which opens each directory along `path`
+/// with `O_NOFOLLOW` so that no symbolic link is ever followed.
+///
+/// `path` must already be checked to be relative and free of `..` components.
+pub(crate) fn openat_beneath_walk(
+    dir_fd: c_int,
+    path: &[u8],
+    flags: c_int,
+    mode: u32,
+) -> Result<c_int, HandleOpenError> {
+    let mut components = path
+        .split(|&c| c == b'/')
+        .filter(|c| !c.is_empty() && *c != b".");
+    let mut name = match components.next() {
+        Some(name) => name,
+        None => {
+            return Err(HandleOpenError::failed_to_open_file_handle_unix_bytes(
+                ENOENT, path,
+            ))
+        }
+    };
+
+    let mut current = dir_fd;
+    let result = loop {
+        let c_name = match CString::new(name) {
+            Ok(c_name) => c_name,
+            Err(_) => {
+                break Err(HandleOpenError::failed_to_open_file_handle_unix_bytes(
+                    -1, path,
+                ))
+            }
+        };
+
+        let next = components.next();
+        let fd = unsafe {
+            match next {
+                Some(_) => openat(
+                    current,
+                    c_name.as_ptr(),
+                    O_RDONLY | O_DIRECTORY | O_NOFOLLOW | O_CLOEXEC,
+                ),
+                None => openat(current, c_name.as_ptr(), flags | O_NOFOLLOW, mode as c_uint),
+            }
+        };
+        if fd < 0 {
+            let code = errno::errno().0;
+            // Some systems report ENOTDIR rather than ELOOP for a symbolic link with O_DIRECTORY.
+            if code == ENOTDIR && next.is_some() && is_symlink_at(current, &c_name) {
+                break Err(HandleOpenError::path_not_beneath_root(path));
+            }
+            break Err(beneath_error(code, path));
+        }
+
+        let next = match next {
+            Some(next) => next,
+            None => break Ok(fd),
+        };
+
+        if current != dir_fd {
+            unsafe { close(current) };
+        }
+        current = fd;
+        name = next;
+    };
+
+    if current != dir_fd {
+        unsafe { close(current) };
+    }
+
+    result
+}
+
+/// Returns whether `name` in the directory `dir_fd` is a symbolic link.
+#[allow(clippy::unnecessary_cast)] // st_mode is wider than mode_t on some platforms
+fn is_symlink_at(dir_fd: c_int, name: &CStr) -> bool {
+    let mut st: stat = unsafe { zeroed() };
+    let ret = unsafe { fstatat(dir_fd, name.as_ptr(), &mut st, AT_SYMLINK_NOFOLLOW) };
+    ret == 0 && (st.st_mode as mode_t & S_IFMT) == S_IFLNK
+}
+
+/// Maps an error from opening beneath a directory, treating a refused symbolic link
+/// or escape as [`HandleOpenError::PathNotBeneathRoot`].
+fn beneath_error(code: c_int, path: &[u8]) -> HandleOpenError {
+    match code {
+        ELOOP | EXDEV => HandleOpenError::path_not_beneath_root(path),
+        // FreeBSD reports EMLINK rather than ELOOP for O_NOFOLLOW on a symbolic link.
+        #[cfg(target_os = "freebsd")]
+        EMLINK => HandleOpenError::path_not_beneath_root(path),
+        code => HandleOpenError::failed_to_open_file_handle_unix_bytes(code, path),
+    }
+}
+
+/// Creates an unnamed temporary file in the directory at `dir`, readable and writable
+/// only by the owner.
+///
+/// On Linux this uses `O_TMPFILE`, so the file never appears in the directory. Where that is
+/// unsupported (other systems, older kernels or some filesystems), a file is created with
+/// `mkstemp` and immediately unlinked instead.
+///
+/// # Errors
+///
+/// Returns a `HandleOpenError` if the file cannot be created.
+pub(crate) fn create_temp_in(dir: &[u8]) -> Result<c_int, HandleOpenError> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    match open_with_flags(dir, O_TMPFILE | O_RDWR | O_CLOEXEC, 0o600) {
+        Ok(fd) => return Ok(fd),
+        // O_TMPFILE not supported by the kernel (EISDIR) or filesystem (EOPNOTSUPP).
+        Err(HandleOpenError::FailedToOpenFileHandle(EISDIR | EOPNOTSUPP, ..)) => {}
+        Err(e) => return Err(e),
+    }
+
+    let (fd, path) = create_unique(dir, b"/.tmp")?;
+    if unsafe { unlink(path.as_ptr()) } != 0 {
+        let code = errno::errno().0;
+        unsafe { close(fd) };
+        return Err(HandleOpenError::failed_to_open_file_handle_unix_bytes(
+            code, dir,
+        ));
+    }
+
+    Ok(fd)
+}
+
+/// Creates a new file named `prefix` + `suffix` + six random characters, readable and
+/// writable only by the owner, using `mkstemp`.
+///
+/// # Returns
+///
+/// The descriptor of the new file (with close-on-exec set), and its path.
+///
+/// # Errors
+///
+/// Returns a `HandleOpenError` if the file cannot be created.
+pub(crate) fn create_unique(
+    prefix: &[u8],
+    suffix: &[u8],
+) -> Result<(c_int, CString), HandleOpenError> {
+    let mut template = Vec::with_capacity(prefix.len() + suffix.len() + 6);
+    template.extend_from_slice(prefix);
+    template.extend_from_slice(suffix);
+    template.extend_from_slice(b"XXXXXX");
+    let template = CString::new(template)
+        .map_err(|_| HandleOpenError::failed_to_open_file_handle_unix_bytes(-1, prefix))?;
+
+    let template = template.into_raw();
+    let fd = unsafe { mkstemp(template) };
+    let template = unsafe { CString::from_raw(template) };
+    if fd < 0 {
+        return Err(HandleOpenError::failed_to_open_file_handle_unix_bytes(
+            errno::errno().0,
+            prefix,
+        ));
+    }
+
+    unsafe { fcntl(fd, F_SETFD, FD_CLOEXEC) };
+    Ok((fd, template))
+}
+
+/// Flushes all data and metadata of the file to the storage device (`fsync`).
+///
+/// On Apple platforms `fsync` does not flush the drive's cache, so `F_FULLFSYNC` is used instead.
+pub(crate) fn sync_all(fd: c_int) -> Result<(), HandleIoError> {
+    #[cfg(target_vendor = "apple")]
+    let ret = unsafe { fcntl(fd, F_FULLFSYNC) };
+    #[cfg(not(target_vendor = "apple"))]
+    let ret = unsafe { fsync(fd) };
+
+    if ret == -1 {
+        return Err(HandleIoError::FailedToSync(errno::errno().0));
+    }
+
+    Ok(())
+}
+
+/// Flushes the data of the file to the storage device (`fdatasync`), along with
+/// only the metadata needed to read it back (such as the file size).
+///
+/// Apple platforms have no `fdatasync`, so this is the same as [`sync_all`] there.
+pub(crate) fn sync_data(fd: c_int) -> Result<(), HandleIoError> {
+    #[cfg(target_vendor = "apple")]
+    {
+        sync_all(fd)
+    }
+
+    #[cfg(not(target_vendor = "apple"))]
+    {
+        if unsafe { fdatasync(fd) } == -1 {
+            return Err(HandleIoError::FailedToSync(errno::errno().0));
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes back a range of the file (`sync_file_range`) on Linux.
+/// Elsewhere, falls back to [`sync_data`] for the whole file.
+///
+/// # Arguments
+///
+/// * `offset` - Start of the range.
+/// * `len` - Length of the range, or 0 for everything from `offset` to the end of the file.
+/// * `flags` - Which parts of the writeback to perform and wait for.
+#[allow(unused_variables)]
+pub(crate) fn sync_range(
+    fd: c_int,
+    offset: u64,
+    len: u64,
+    flags: super::SyncRangeFlags,
+) -> Result<(), HandleIoError> {
+    if flags.is_empty() {
+        return Ok(());
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        use super::SyncRangeFlags;
+
+        let mut raw = 0;
+        if flags.contains(SyncRangeFlags::WAIT_BEFORE) {
+            raw |= SYNC_FILE_RANGE_WAIT_BEFORE;
+        }
+        if flags.contains(SyncRangeFlags::WRITE) {
+            raw |= SYNC_FILE_RANGE_WRITE;
+        }
+        if flags.contains(SyncRangeFlags::WAIT_AFTER) {
+            raw |= SYNC_FILE_RANGE_WAIT_AFTER;
+        }
+
+        let ret = unsafe { sync_file_range(fd, offset as off64_t, len as off64_t, raw) };
+        if ret == -1 {
+            return Err(HandleIoError::FailedToSync(errno::errno().0));
+        }
+
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        sync_data(fd)
+    }
+}
+
+/// Flushes the directory containing `path` to the storage device, so that
+/// a preceding rename or link within it is durable.
+pub(crate) fn sync_parent_dir(path: &[u8]) -> Result<(), HandleIoError> {
+    let parent: &[u8] = match path.iter().rposition(|&c| c == b'/') {
+        Some(0) => b"/",
+        Some(x) => &path[..x],
+        None => b".",
+    };
+
+    let parent = CString::new(parent).map_err(|_| HandleIoError::FailedToSync(EINVAL))?;
+    let fd = unsafe { open(parent.as_ptr(), O_RDONLY | O_DIRECTORY | O_CLOEXEC) };
+    if fd < 0 {
+        return Err(HandleIoError::FailedToSync(errno::errno().0));
+    }
+
+    let result = sync_all(fd);
+    unsafe { close(fd) };
+    result
+}
+
+/// Gives an unnamed file created with `O_TMPFILE` a name, by linking it at `path`.
+///
+/// Files created by the `mkstemp` fallback of [`create_temp_in`] cannot be linked,
+/// as they were deleted rather than created unnamed.
+///
+/// # Errors
+///
+/// Returns a [`HandleIoError::FailedToPersist`] if the file cannot be linked,
+/// for example because `path` already exists.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn persist(fd: c_int, path: &[u8]) -> Result<(), HandleIoError> {
+    let c_path = CString::new(path).map_err(|_| HandleIoError::FailedToPersist(EINVAL))?;
+
+    // Linking through /proc works without CAP_DAC_READ_SEARCH, unlike AT_EMPTY_PATH.
+    let proc_path = proc_fd_path(fd);
+
+    let ret = unsafe {
+        linkat(
+            AT_FDCWD,
+            proc_path.as_ptr() as *const c_char,
+            AT_FDCWD,
+            c_path.as_ptr(),
+            AT_SYMLINK_FOLLOW,
+        )
+    };
+    if ret != 0 {
+        return Err(HandleIoError::FailedToPersist(errno::errno().0));
+    }
+
+    Ok(())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const PROC_FD_PREFIX: &[u8] = b"/proc/self/fd/";
+
+/// Builds the NUL-terminated `/proc/self/fd/<fd>` path of a file descriptor, without allocating.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn proc_fd_path(fd: c_int) -> [u8; PROC_FD_PREFIX.len() + 11] {
+    // Prefix, up to 10 digits of a non-negative c_int, and the NUL terminator.
+    let mut path = [0u8; PROC_FD_PREFIX.len() + 11];
+    path[..PROC_FD_PREFIX.len()].copy_from_slice(PROC_FD_PREFIX);
+
+    let mut digits = [0u8; 10];
+    let mut len = 0;
+    let mut value = fd as u32;
+    loop {
+        digits[len] = b'0' + (value % 10) as u8;
+        len += 1;
+        value /= 10;
+        if value == 0 {
+            break;
+        }
+    }
+    for (x, digit) in digits[..len].iter().rev().enumerate() {
+        path[PROC_FD_PREFIX.len() + x] = *digit;
+    }
+
+    path
+}
+
+/// Duplicates the file descriptor, with the close-on-exec flag set on the new descriptor.
+///
+/// The new descriptor refers to the same open file (including its offset and status flags).
+pub(crate) fn duplicate_fd(fd: c_int) -> Result<c_int, HandleOpenError> {
+    let new_fd = unsafe { fcntl(fd, F_DUPFD_CLOEXEC, 0) };
+    if new_fd < 0 {
+        return Err(HandleOpenError::FailedToDuplicateHandle(errno::errno().0));
+    }
+
+    Ok(new_fd)
+}
+
+/// Opens the file behind `fd` again, read-only, through its `/proc/self/fd` link.
+///
+/// Unlike reopening by path, this refers to the same file even if it has since been renamed
+/// or replaced. The new descriptor has its own offset, and the close-on-exec flag set.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn reopen_read_only(fd: c_int) -> Result<c_int, HandleOpenError> {
+    let path = proc_fd_path(fd);
+    let new_fd = unsafe { open(path.as_ptr() as *const c_char, O_RDONLY | O_CLOEXEC) };
+    if new_fd < 0 {
+        return Err(HandleOpenError::FailedToDuplicateHandle(errno::errno().0));
+    }
+
+    Ok(new_fd)
+}
+
 #[cfg(unix)]
 #[allow(clippy::unnecessary_cast)] // st_size type varies across Unix platforms
 pub fn get_file_size(fd: c_int) -> Result<i64, HandleOpenError> {
@@ -67,42 +547,880 @@ pub fn get_file_size(fd: c_int) -> Result<i64, HandleOpenError> {
     }
 }
 
+/// Gets the metadata of the file.
+///
+/// Uses `statx` on Linux and Android, which also reports the creation time, falling back
+/// to `fstat` where `statx` is unavailable (old kernels, or blocked by a seccomp filter).
+///
+/// # Errors
+///
+/// Returns a `HandleIoError` if the metadata cannot be queried.
+pub(crate) fn get_metadata(fd: c_int) -> Result<super::FileMetadata, HandleIoError> {
+    #[cfg(any(target_env = "gnu", target_os = "android"))]
+    {
+        let mut stx: libc::statx = unsafe { zeroed() };
+        let ret = unsafe {
+            statx(
+                fd,
+                c"".as_ptr(),
+                AT_EMPTY_PATH,
+                STATX_BASIC_STATS | STATX_BTIME,
+                &mut stx,
+            )
+        };
+
+        if ret == 0 {
+            return Ok(metadata_from_statx(&stx));
+        }
+
+        match errno::errno().0 {
+            ENOSYS | EPERM | EINVAL => {}
+            code => return Err(HandleIoError::FailedToGetMetadata(code)),
+        }
+    }
+
+    #[cfg(target_env = "gnu")]
+    use libc::{fstat64 as fstat, stat64 as stat};
+
+    let mut st: stat = unsafe { zeroed() };
+    if unsafe { fstat(fd, &mut st) } == -1 {
+        return Err(HandleIoError::FailedToGetMetadata(errno::errno().0));
+    }
+
+    Ok(metadata_from_stat(&st))
+}
+
+#[cfg(any(target_env = "gnu", target_os = "android"))]
+#[allow(clippy::unnecessary_cast)] // dev_t differs between targets
+fn metadata_from_statx(stx: &libc::statx) -> super::FileMetadata {
+    let timestamp = |ts: &statx_timestamp| super::FileTimestamp {
+        secs: ts.tv_sec,
+        nanos: ts.tv_nsec,
+    };
+
+    super::FileMetadata {
+        size: stx.stx_size,
+        blocks: stx.stx_blocks,
+        block_size: stx.stx_blksize as u64,
+        mode: stx.stx_mode as u32,
+        device: makedev(stx.stx_dev_major, stx.stx_dev_minor) as u64,
+        inode: stx.stx_ino,
+        modified: timestamp(&stx.stx_mtime),
+        changed: timestamp(&stx.stx_ctime),
+        created: (stx.stx_mask & STATX_BTIME != 0).then(|| timestamp(&stx.stx_btime)),
+    }
+}
+
+#[allow(clippy::unnecessary_cast)] // Field types of `stat` differ between targets
+fn metadata_from_stat(
+    #[cfg(target_env = "gnu")] st: &stat64,
+    #[cfg(not(target_env = "gnu"))] st: &stat,
+) -> super::FileMetadata {
+    let timestamp = |secs: time_t, nanos: c_long| super::FileTimestamp {
+        secs: secs as i64,
+        nanos: nanos as u32,
+    };
+
+    #[cfg(any(target_vendor = "apple", target_os = "freebsd", target_os = "netbsd"))]
+    let created = Some(timestamp(st.st_birthtime, st.st_birthtime_nsec));
+    #[cfg(not(any(target_vendor = "apple", target_os = "freebsd", target_os = "netbsd")))]
+    let created = None;
+
+    super::FileMetadata {
+        size: st.st_size as u64,
+        blocks: st.st_blocks as u64,
+        block_size: st.st_blksize as u64,
+        mode: st.st_mode as u32,
+        device: st.st_dev as u64,
+        inode: st.st_ino as u64,
+        modified: timestamp(st.st_mtime, st.st_mtime_nsec),
+        changed: timestamp(st.st_ctime, st.st_ctime_nsec),
+        created,
+    }
+}
+
+/// Sets the size of the file, growing it with [`grow_file`] or shrinking it with `ftruncate`.
 #[cfg(unix)]
 #[allow(clippy::comparison_chain)]
 pub(crate) fn set_file_size(fd: c_int, size: i64) -> Result<(), HandleOpenError> {
-    unsafe {
-        // Get current file size
-        let current_size = get_file_size(fd)?;
+    // Get current file size
+    let current_size = get_file_size(fd)?;
+
+    if size > current_size {
+        grow_file(fd, size)?;
+    } else if size < current_size {
+        // Shrink using ftruncate on all platforms
+        if unsafe { truncate_fd(fd, size) } != 0 {
+            return Err(HandleOpenError::failed_to_set_file_size(errno::errno().0));
+        }
+    }
+    Ok(())
+}
+
+/// Grows the file to `size` bytes, reserving disk space for the new region where supported.
+///
+/// Uses `fallocate` on Linux and `posix_fallocate` on FreeBSD/DragonFly. If the filesystem
+/// cannot reserve space (`EOPNOTSUPP`, e.g. some tmpfs variants or older filesystems; or
+/// `EINVAL`, as returned by `posix_fallocate` on ZFS), falls back to `ftruncate`, which
+/// extends the file without reserving space. Other Unix systems, including macOS, always
+/// use `ftruncate`.
+///
+/// The file must currently be smaller than `size`.
+#[cfg(unix)]
+pub(crate) fn grow_file(fd: c_int, size: i64) -> Result<(), HandleOpenError> {
+    #[cfg(target_os = "linux")]
+    {
+        // Linux specific fallocate - allocates without zeroing
+        #[cfg(target_env = "gnu")]
+        let ret = unsafe { fallocate64(fd, 0, 0, size as off64_t) };
+        #[cfg(not(target_env = "gnu"))]
+        let ret = unsafe { fallocate(fd, 0, 0, size as off_t) };
+        if ret == 0 {
+            return Ok(());
+        }
+
+        let err = errno::errno().0;
+        if err != EOPNOTSUPP && err != EINVAL {
+            return Err(HandleOpenError::failed_to_set_file_size(err));
+        }
+    }
+
+    #[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+    {
+        // Some BSDs support posix_fallocate
+        let ret = unsafe { libc::posix_fallocate(fd, 0, size as off_t) };
+        if ret == 0 {
+            return Ok(());
+        }
+
+        if ret != EOPNOTSUPP && ret != EINVAL {
+            return Err(HandleOpenError::failed_to_set_file_size(ret));
+        }
+    }
 
-        if size > current_size {
-            #[cfg(target_os = "linux")]
+    // No space reservation available - just extend the file
+    if unsafe { truncate_fd(fd, size) } != 0 {
+        return Err(HandleOpenError::failed_to_set_file_size(errno::errno().0));
+    }
+
+    Ok(())
+}
+
+/// Calls `ftruncate`, or `ftruncate64` where `off_t` may be 32 bits.
+unsafe fn truncate_fd(fd: c_int, size: i64) -> c_int {
+    #[cfg(any(target_env = "gnu", target_os = "android"))]
+    {
+        ftruncate64(fd, size as off64_t)
+    }
+    #[cfg(not(any(target_env = "gnu", target_os = "android")))]
+    {
+        ftruncate(fd, size as off_t)
+    }
+}
+
+/// Deallocates a range of the file (`FALLOC_FL_PUNCH_HOLE | FALLOC_FL_KEEP_SIZE`).
+pub(crate) fn punch_hole(fd: c_int, offset: u64, len: u64) -> Result<(), HandleIoError> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        fallocate_range(fd, FALLOC_FL_PUNCH_HOLE | FALLOC_FL_KEEP_SIZE, offset, len)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    {
+        let _ = (fd, offset, len);
+        Err(HandleIoError::Unsupported)
+    }
+}
+
+/// Zeroes a range of the file without writing (`FALLOC_FL_ZERO_RANGE | FALLOC_FL_KEEP_SIZE`).
+pub(crate) fn zero_range(fd: c_int, offset: u64, len: u64) -> Result<(), HandleIoError> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        fallocate_range(fd, FALLOC_FL_ZERO_RANGE | FALLOC_FL_KEEP_SIZE, offset, len)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    {
+        let _ = (fd, offset, len);
+        Err(HandleIoError::Unsupported)
+    }
+}
+
+/// Reserves disk space for a range of the file without changing its size (`FALLOC_FL_KEEP_SIZE`).
+pub(crate) fn preallocate_keep_size(fd: c_int, offset: u64, len: u64) -> Result<(), HandleIoError> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        fallocate_range(fd, FALLOC_FL_KEEP_SIZE, offset, len)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    {
+        let _ = (fd, offset, len);
+        Err(HandleIoError::Unsupported)
+    }
+}
+
+/// Calls `fallocate` with the given `FALLOC_FL_*` mode on a range of the file.
+///
+/// # Errors
+///
+/// Returns [`HandleIoError::Unsupported`] if the kernel or filesystem does not support
+/// the requested mode.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn fallocate_range(fd: c_int, mode: c_int, offset: u64, len: u64) -> Result<(), HandleIoError> {
+    loop {
+        let ret = unsafe {
+            #[cfg(any(target_env = "gnu", target_os = "android"))]
             {
-                // Linux specific fallocate - allocates without zeroing
-                if fallocate(fd, 0, 0, size as off_t) != 0 {
-                    return Err(HandleOpenError::failed_to_set_file_size(errno::errno().0));
-                }
+                fallocate64(fd, mode, offset as off64_t, len as off64_t)
             }
-            #[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+            #[cfg(not(any(target_env = "gnu", target_os = "android")))]
             {
-                // Some BSDs support posix_fallocate
-                let ret = libc::posix_fallocate(fd, 0, size as off_t);
-                if ret != 0 {
-                    return Err(HandleOpenError::failed_to_set_file_size(ret));
+                fallocate(fd, mode, offset as off_t, len as off_t)
+            }
+        };
+
+        if ret == 0 {
+            return Ok(());
+        }
+
+        match errno::errno().0 {
+            EINTR => continue,
+            EOPNOTSUPP | ENOSYS => return Err(HandleIoError::Unsupported),
+            err => return Err(HandleIoError::FailedToAllocate(err)),
+        }
+    }
+}
+
+/// Finds the start of the next data region (`SEEK_DATA`) or hole (`SEEK_HOLE`) at or after `offset`.
+///
+/// Note this moves the file position, which the handles do not otherwise use.
+///
+/// # Returns
+///
+/// The offset found, or `None` if there is no data at or after `offset` (`ENXIO`).
+///
+/// # Errors
+///
+/// Returns [`HandleIoError::Unsupported`] if the filesystem does not support `SEEK_DATA`/`SEEK_HOLE`.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_vendor = "apple",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "solaris",
+    target_os = "illumos"
+))]
+pub(crate) fn seek_extent(
+    fd: c_int,
+    offset: u64,
+    whence: c_int,
+) -> Result<Option<u64>, HandleIoError> {
+    // off_t is 32 bits on 32-bit Android, so use the 64-bit variant there too.
+    let ret = unsafe {
+        #[cfg(any(target_env = "gnu", target_os = "android"))]
+        {
+            lseek64(fd, offset as off64_t, whence) as i64
+        }
+        #[cfg(not(any(target_env = "gnu", target_os = "android")))]
+        {
+            lseek(fd, offset as off_t, whence) as i64
+        }
+    };
+
+    if ret >= 0 {
+        return Ok(Some(ret as u64));
+    }
+
+    match errno::errno().0 {
+        ENXIO => Ok(None),
+        EINVAL => Err(HandleIoError::Unsupported),
+        err => Err(HandleIoError::FailedToQueryExtents(err)),
+    }
+}
+
+/// `FS_IOC_FIEMAP`, i.e. `_IOWR('f', 11, struct fiemap)`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const FS_IOC_FIEMAP: u32 = 0xC020_660B;
+
+/// Header of `struct fiemap` from `linux/fiemap.h`, followed in memory by `fm_extent_count` extents.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+struct Fiemap {
+    fm_start: u64,
+    fm_length: u64,
+    fm_flags: u32,
+    fm_mapped_extents: u32,
+    fm_extent_count: u32,
+    fm_reserved: u32,
+}
+
+/// `struct fiemap_extent` from `linux/fiemap.h`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct FiemapExtent {
+    fe_logical: u64,
+    fe_physical: u64,
+    fe_length: u64,
+    fe_reserved64: [u64; 2],
+    fe_flags: u32,
+    fe_reserved: [u32; 3],
+}
+
+/// `FIEMAP_EXTENT_LAST`: this is the last extent in the file.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const FIEMAP_EXTENT_LAST: u32 = 0x0000_0001;
+
+/// Queries the physical layout of the file on disk with the `FS_IOC_FIEMAP` ioctl.
+///
+/// # Errors
+///
+/// Returns [`HandleIoError::Unsupported`] if the filesystem does not support `FIEMAP`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn fiemap(fd: c_int) -> Result<Vec<super::PhysicalExtent>, HandleIoError> {
+    const BATCH: usize = 64;
+
+    #[repr(C)]
+    struct Request {
+        header: Fiemap,
+        extents: [FiemapExtent; BATCH],
+    }
+
+    let mut result = Vec::new();
+    let mut start = 0u64;
+    loop {
+        let mut request: Request = unsafe { zeroed() };
+        request.header.fm_start = start;
+        request.header.fm_length = u64::MAX - start;
+        request.header.fm_extent_count = BATCH as u32;
+
+        if unsafe { ioctl(fd, FS_IOC_FIEMAP as _, &mut request as *mut Request) } != 0 {
+            return Err(match errno::errno().0 {
+                EOPNOTSUPP | ENOTTY => HandleIoError::Unsupported,
+                err => HandleIoError::FailedToQueryExtents(err),
+            });
+        }
+
+        let mapped = request.header.fm_mapped_extents as usize;
+        for extent in &request.extents[..mapped.min(BATCH)] {
+            result.push(super::PhysicalExtent {
+                logical: extent.fe_logical,
+                physical: extent.fe_physical,
+                len: extent.fe_length,
+                flags: extent.fe_flags,
+            });
+        }
+
+        match request.extents[..mapped.min(BATCH)].last() {
+            Some(last) if last.fe_flags & FIEMAP_EXTENT_LAST == 0 => {
+                start = last.fe_logical + last.fe_length;
+            }
+            _ => return Ok(result),
+        }
+    }
+}
+
+/// Gives the operating system a hint about how a range of the file will be accessed.
+///
+/// Uses `posix_fadvise` where available, and `F_RDADVISE` for [`super::FileAdvice::WillNeed`]
+/// on Apple platforms. Elsewhere, the hint is ignored. A `len` of 0 covers the range
+/// from `offset` to the end of the file.
+///
+/// # Errors
+///
+/// Returns a `HandleIoError` if the hint is rejected, e.g. because the range is invalid.
+pub(crate) fn advise(
+    fd: c_int,
+    offset: u64,
+    len: u64,
+    advice: super::FileAdvice,
+) -> Result<(), HandleIoError> {
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "illumos"
+    ))]
+    {
+        use super::FileAdvice;
+
+        let advice = match advice {
+            FileAdvice::Normal => POSIX_FADV_NORMAL,
+            FileAdvice::Sequential => POSIX_FADV_SEQUENTIAL,
+            FileAdvice::Random => POSIX_FADV_RANDOM,
+            FileAdvice::WillNeed => POSIX_FADV_WILLNEED,
+            FileAdvice::DontNeed => POSIX_FADV_DONTNEED,
+            FileAdvice::NoReuse => POSIX_FADV_NOREUSE,
+        };
+
+        // Unlike most calls, posix_fadvise returns the error code rather than setting errno.
+        #[cfg(any(target_env = "gnu", target_os = "android"))]
+        let ret = unsafe { posix_fadvise64(fd, offset as off64_t, len as off64_t, advice) };
+
+        #[cfg(not(any(target_env = "gnu", target_os = "android")))]
+        let ret = unsafe { posix_fadvise(fd, offset as off_t, len as off_t, advice) };
+
+        if ret != 0 {
+            return Err(HandleIoError::FailedToAdvise(ret));
+        }
+    }
+
+    #[cfg(target_vendor = "apple")]
+    if advice == super::FileAdvice::WillNeed {
+        let len = if len == 0 {
+            (get_file_size(fd).unwrap_or(0).max(0) as u64).saturating_sub(offset)
+        } else {
+            len
+        };
+
+        // F_RDADVISE takes an `int` count, so large ranges are advised in chunks.
+        let mut advised = 0;
+        while advised < len {
+            let count = (len - advised).min(c_int::MAX as u64);
+            let mut ra = radvisory {
+                ra_offset: (offset + advised) as off_t,
+                ra_count: count as c_int,
+            };
+
+            if unsafe { fcntl(fd, F_RDADVISE, &mut ra as *mut radvisory) } == -1 {
+                return Err(HandleIoError::FailedToAdvise(errno::errno().0));
+            }
+
+            advised += count;
+        }
+    }
+
+    #[cfg(not(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "illumos",
+        target_vendor = "apple"
+    )))]
+    let _ = (fd, offset, len, advice);
+
+    Ok(())
+}
+
+/// Starts reading a range of the file into the page cache, without waiting for it.
+///
+/// Uses `readahead` on Linux, and [`advise`] with [`super::FileAdvice::WillNeed`] elsewhere.
+///
+/// # Errors
+///
+/// Returns a `HandleIoError` if the request is rejected.
+pub(crate) fn readahead(fd: c_int, offset: u64, len: u64) -> Result<(), HandleIoError> {
+    #[cfg(target_os = "linux")]
+    {
+        let len = len.min(isize::MAX as u64) as size_t;
+        if unsafe { libc::readahead(fd, offset as off64_t, len) } == -1 {
+            return Err(HandleIoError::FailedToAdvise(errno::errno().0));
+        }
+
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        advise(fd, offset, len, super::FileAdvice::WillNeed)
+    }
+}
+
+/// `FICLONERANGE`, i.e. `_IOW(0x94, 13, struct file_clone_range)`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const FICLONERANGE: u32 = 0x4020_940D;
+
+/// `struct file_clone_range` from `linux/fs.h`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+struct FileCloneRange {
+    src_fd: i64,
+    src_offset: u64,
+    src_length: u64,
+    dest_offset: u64,
+}
+
+/// Shares a range of `src` into `dst` with `FICLONERANGE`, so no data is copied.
+///
+/// Returns `false` if the range could not be cloned, e.g. because the filesystem does not
+/// support reflinks, the files are on different filesystems, or the offsets are not block-aligned.
+/// `len` must be non-zero, as a length of 0 means "to the end of `src`" for the ioctl.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn clone_range(
+    src: c_int,
+    src_offset: u64,
+    dst: c_int,
+    dst_offset: u64,
+    len: u64,
+) -> bool {
+    let range = FileCloneRange {
+        src_fd: src as i64,
+        src_offset,
+        src_length: len,
+        dest_offset: dst_offset,
+    };
+
+    unsafe { ioctl(dst, FICLONERANGE as _, &range as *const FileCloneRange) == 0 }
+}
+
+/// Copies a range of `src` into `dst` with `copy_file_range`, letting the kernel (or
+/// the filesystem, e.g. NFS server-side copy) move the data without a round trip through user space.
+///
+/// Returns the number of bytes copied, which is less than `len` only if the end of `src` was reached.
+///
+/// # Errors
+///
+/// Returns [`HandleIoError::Unsupported`] if nothing was copied because `copy_file_range` is
+/// unavailable for these files (old kernels, different filesystems, etc.), or another
+/// `HandleIoError` if the copy fails.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn copy_file_range(
+    src: c_int,
+    src_offset: u64,
+    dst: c_int,
+    dst_offset: u64,
+    len: u64,
+) -> Result<u64, HandleIoError> {
+    // Called via `syscall`, as the libc wrapper is missing on glibc < 2.27 and older Android.
+    let mut off_in = src_offset as loff_t;
+    let mut off_out = dst_offset as loff_t;
+    let mut copied = 0u64;
+
+    while copied < len {
+        let chunk = (len - copied).min(1 << 30) as size_t;
+        let ret = unsafe {
+            syscall(
+                SYS_copy_file_range,
+                src,
+                &mut off_in as *mut loff_t,
+                dst,
+                &mut off_out as *mut loff_t,
+                chunk,
+                0 as c_uint,
+            )
+        };
+
+        if ret == -1 {
+            match errno::errno().0 {
+                EINTR => continue,
+                ENOSYS | EXDEV | EINVAL | EOPNOTSUPP | EBADF | EPERM if copied == 0 => {
+                    return Err(HandleIoError::Unsupported)
                 }
+                code => return Err(HandleIoError::FailedToCopy(code)),
+            }
+        }
+
+        if ret == 0 {
+            break;
+        }
+
+        copied += ret as u64;
+    }
+
+    Ok(copied)
+}
+
+/// Takes a lock on the whole file using `flock`, or converts an already held one.
+///
+/// # Errors
+///
+/// Returns [`HandleIoError::WouldBlock`] if `blocking` is false and the lock is held
+/// elsewhere, or another `HandleIoError` if locking fails.
+pub(crate) fn lock_file(
+    fd: c_int,
+    kind: super::LockKind,
+    blocking: bool,
+) -> Result<(), HandleIoError> {
+    let mut operation = match kind {
+        super::LockKind::Shared => LOCK_SH,
+        super::LockKind::Exclusive => LOCK_EX,
+    };
+    if !blocking {
+        operation |= LOCK_NB;
+    }
+
+    flock_retrying(fd, operation)
+}
+
+/// Releases the lock on the whole file taken with [`lock_file`].
+pub(crate) fn unlock_file(fd: c_int) -> Result<(), HandleIoError> {
+    flock_retrying(fd, LOCK_UN)
+}
+
+fn flock_retrying(fd: c_int, operation: c_int) -> Result<(), HandleIoError> {
+    loop {
+        if unsafe { flock(fd, operation) } == 0 {
+            return Ok(());
+        }
+
+        match errno::errno().0 {
+            EINTR => continue,
+            EWOULDBLOCK => return Err(HandleIoError::WouldBlock),
+            code => return Err(HandleIoError::FailedToLock(code)),
+        }
+    }
+}
+
+/// Takes (`Some(kind)`) or releases (`None`) a lock on a byte range of the file.
+///
+/// Uses open file description locks (`F_OFD_SETLK`) on Linux and Android, which belong to
+/// the open file rather than the process, falling back to classic process-associated
+/// `fcntl` locks elsewhere, or on kernels older than 3.15.
+///
+/// A `len` of 0 covers the range from `offset` to the end of the file, however large it grows.
+///
+/// # Errors
+///
+/// Returns [`HandleIoError::WouldBlock`] if `blocking` is false and the range is locked
+/// elsewhere, [`HandleIoError::FailedToLock`] with `EOVERFLOW` if `offset` or `len` does not
+/// fit in a signed file offset, or another `HandleIoError` if locking fails.
+pub(crate) fn lock_range(
+    fd: c_int,
+    offset: u64,
+    len: u64,
+    kind: Option<super::LockKind>,
+    blocking: bool,
+) -> Result<(), HandleIoError> {
+    if offset > range_lock::Offset::MAX as u64 || len > range_lock::Offset::MAX as u64 {
+        return Err(HandleIoError::FailedToLock(EOVERFLOW));
+    }
+
+    let mut lock: range_lock::Lock = unsafe { zeroed() };
+    lock.l_type = match kind {
+        Some(super::LockKind::Shared) => F_RDLCK,
+        Some(super::LockKind::Exclusive) => F_WRLCK,
+        None => F_UNLCK,
+    } as _;
+    lock.l_whence = SEEK_SET as _;
+    lock.l_start = offset as range_lock::Offset;
+    lock.l_len = len as range_lock::Offset;
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let command = if blocking { F_OFD_SETLKW } else { F_OFD_SETLK };
+        match fcntl_lock_retrying(fd, command, &mut lock) {
+            Err(HandleIoError::FailedToLock(EINVAL)) => {}
+            result => return result,
+        }
+    }
+
+    let command = if blocking {
+        range_lock::SETLKW
+    } else {
+        range_lock::SETLK
+    };
+    fcntl_lock_retrying(fd, command, &mut lock)
+}
+
+fn fcntl_lock_retrying(
+    fd: c_int,
+    command: c_int,
+    lock: &mut range_lock::Lock,
+) -> Result<(), HandleIoError> {
+    loop {
+        if unsafe { range_lock::set(fd, command, lock) } == 0 {
+            return Ok(());
+        }
+
+        match errno::errno().0 {
+            EINTR => continue,
+            EAGAIN | EACCES => return Err(HandleIoError::WouldBlock),
+            code => return Err(HandleIoError::FailedToLock(code)),
+        }
+    }
+}
+
+/// Byte range locks with `struct flock`, where `off_t` is wide enough for any file offset,
+/// or ranges beyond it are rejected.
+#[cfg(not(all(
+    target_env = "gnu",
+    target_pointer_width = "32",
+    any(target_arch = "x86", target_arch = "arm")
+)))]
+mod range_lock {
+    use libc::*;
+
+    pub(super) type Lock = flock;
+    pub(super) type Offset = off_t;
+    pub(super) const SETLK: c_int = F_SETLK;
+    pub(super) const SETLKW: c_int = F_SETLKW;
+
+    pub(super) unsafe fn set(fd: c_int, command: c_int, lock: &mut Lock) -> c_int {
+        fcntl(fd, command, lock as *mut Lock)
+    }
+}
+
+/// Byte range locks on 32-bit glibc, where `off_t` is 32 bits. Uses `struct flock64` with the
+/// `fcntl64` syscall directly, as glibc's `fcntl` expects a `struct flock` for the OFD commands.
+#[cfg(all(
+    target_env = "gnu",
+    target_pointer_width = "32",
+    any(target_arch = "x86", target_arch = "arm")
+))]
+mod range_lock {
+    use libc::*;
+
+    pub(super) type Lock = flock64;
+    pub(super) type Offset = off64_t;
+    /// `F_SETLK64` and `F_SETLKW64`, which libc does not export.
+    pub(super) const SETLK: c_int = 13;
+    pub(super) const SETLKW: c_int = 14;
+
+    pub(super) unsafe fn set(fd: c_int, command: c_int, lock: &mut Lock) -> c_int {
+        syscall(SYS_fcntl64, fd, command, lock as *mut Lock) as c_int
+    }
+}
+
+/// Reads from the file at the given offset, without changing the file position.
+///
+/// Retries if interrupted by a signal. May read fewer bytes than requested;
+/// a return value of 0 indicates the end of the file.
+pub(crate) fn read_at(fd: c_int, offset: u64, buf: &mut [u8]) -> Result<usize, HandleIoError> {
+    let len = buf.len().min(isize::MAX as usize);
+    loop {
+        let ret = unsafe {
+            #[cfg(any(target_env = "gnu", target_os = "android"))]
+            {
+                pread64(fd, buf.as_mut_ptr() as *mut c_void, len, offset as off64_t)
             }
-            #[cfg(not(any(target_os = "linux", target_os = "freebsd", target_os = "dragonfly")))]
+            #[cfg(not(any(target_env = "gnu", target_os = "android")))]
             {
-                // Other Unix systems including macOS - have to use ftruncate
-                if ftruncate(fd, size as off_t) != 0 {
-                    return Err(HandleOpenError::failed_to_set_file_size(errno::errno().0));
+                pread(fd, buf.as_mut_ptr() as *mut c_void, len, offset as off_t)
+            }
+        };
+
+        if ret >= 0 {
+            return Ok(ret as usize);
+        }
+
+        let err = errno::errno().0;
+        if err != EINTR {
+            return Err(HandleIoError::FailedToRead(err));
+        }
+    }
+}
+
+/// Writes to the file at the given offset, without changing the file position.
+///
+/// Retries if interrupted by a signal. May write fewer bytes than requested.
+pub(crate) fn write_at(fd: c_int, offset: u64, buf: &[u8]) -> Result<usize, HandleIoError> {
+    let len = buf.len().min(isize::MAX as usize);
+    loop {
+        let ret = unsafe {
+            #[cfg(any(target_env = "gnu", target_os = "android"))]
+            {
+                pwrite64(fd, buf.as_ptr() as *const c_void, len, offset as off64_t)
+            }
+            #[cfg(not(any(target_env = "gnu", target_os = "android")))]
+            {
+                pwrite(fd, buf.as_ptr() as *const c_void, len, offset as off_t)
+            }
+        };
+
+        if ret >= 0 {
+            return Ok(ret as usize);
+        }
+
+        let err = errno::errno().0;
+        if err != EINTR {
+            return Err(HandleIoError::FailedToWrite(err));
+        }
+    }
+}
+
+/// Maximum number of buffers passed to a single `preadv`/`pwritev` call.
+/// This is the minimum `IOV_MAX` guaranteed on Linux.
+#[cfg(all(feature = "std", target_os = "linux"))]
+const MAX_IOV: usize = 1024;
+
+/// Reads from the file at the given offset into multiple buffers, in order.
+///
+/// Uses `preadv` on Linux; on other platforms, falls back to one read per buffer.
+#[cfg(feature = "std")]
+pub(crate) fn read_vectored_at(
+    fd: c_int,
+    offset: u64,
+    bufs: &mut [std::io::IoSliceMut<'_>],
+) -> Result<usize, HandleIoError> {
+    #[cfg(target_os = "linux")]
+    {
+        // IoSliceMut is guaranteed to be ABI compatible with iovec on Unix.
+        let count = bufs.len().min(MAX_IOV) as c_int;
+        loop {
+            let ret = unsafe {
+                #[cfg(target_env = "gnu")]
+                {
+                    preadv64(fd, bufs.as_ptr() as *const iovec, count, offset as off64_t)
+                }
+                #[cfg(not(target_env = "gnu"))]
+                {
+                    preadv(fd, bufs.as_ptr() as *const iovec, count, offset as off_t)
                 }
+            };
+
+            if ret >= 0 {
+                return Ok(ret as usize);
             }
-        } else if size < current_size {
-            // Shrink using ftruncate on all platforms
-            if ftruncate(fd, size as off_t) != 0 {
-                return Err(HandleOpenError::failed_to_set_file_size(errno::errno().0));
+
+            let err = errno::errno().0;
+            if err != EINTR {
+                return Err(HandleIoError::FailedToRead(err));
             }
         }
     }
-    Ok(())
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        super::vectored::read_vectored_fallback(offset, bufs, |offset, buf| {
+            read_at(fd, offset, buf)
+        })
+    }
+}
+
+/// Writes multiple buffers, in order, to the file at the given offset.
+///
+/// Uses `pwritev` on Linux; on other platforms, falls back to one write per buffer.
+#[cfg(feature = "std")]
+pub(crate) fn write_vectored_at(
+    fd: c_int,
+    offset: u64,
+    bufs: &[std::io::IoSlice<'_>],
+) -> Result<usize, HandleIoError> {
+    #[cfg(target_os = "linux")]
+    {
+        // IoSlice is guaranteed to be ABI compatible with iovec on Unix.
+        let count = bufs.len().min(MAX_IOV) as c_int;
+        loop {
+            let ret = unsafe {
+                #[cfg(target_env = "gnu")]
+                {
+                    pwritev64(fd, bufs.as_ptr() as *const iovec, count, offset as off64_t)
+                }
+                #[cfg(not(target_env = "gnu"))]
+                {
+                    pwritev(fd, bufs.as_ptr() as *const iovec, count, offset as off_t)
+                }
+            };
+
+            if ret >= 0 {
+                return Ok(ret as usize);
+            }
+
+            let err = errno::errno().0;
+            if err != EINTR {
+                return Err(HandleIoError::FailedToWrite(err));
+            }
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        super::vectored::write_vectored_fallback(offset, bufs, |offset, buf| {
+            write_at(fd, offset, buf)
+        })
+    }
 }
@@ -0,0 +1,162 @@
+use super::HandleIoError;
+use core::ops::Deref;
+use std::io::{IoSlice, IoSliceMut};
+
+/// The result of a vectored read or write, such as [`ReadOnlyFileHandle::read_vectored_at`].
+///
+/// Buffers are always filled (or drained) in order, so the number of bytes transferred
+/// to each individual buffer can be recovered with [`Self::per_buffer`].
+///
+/// [`ReadOnlyFileHandle::read_vectored_at`]: super::ReadOnlyFileHandle::read_vectored_at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VectoredTransfer {
+    total: usize,
+}
+
+impl VectoredTransfer {
+    pub(crate) fn new(total: usize) -> Self {
+        VectoredTransfer { total }
+    }
+
+    /// Returns the total number of bytes transferred across all buffers.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Returns the number of bytes transferred to or from each buffer, in order.
+    ///
+    /// # Arguments
+    ///
+    /// * `bufs` - The same buffers which were passed to the vectored operation.
+    pub fn per_buffer<'a, B: Deref<Target = [u8]>>(
+        &self,
+        bufs: &'a [B],
+    ) -> impl Iterator<Item = usize> + 'a {
+        let mut remaining = self.total;
+        bufs.iter().map(move |buf| {
+            let transferred = buf.len().min(remaining);
+            remaining -= transferred;
+            transferred
+        })
+    }
+}
+
+/// Emulates a vectored read with one positional read per buffer.
+///
+/// Stops at the first short read, so the buffers are filled in order as with `preadv`.
+/// An error after some bytes were read ends the read early, returning those bytes.
+#[allow(dead_code)] // Unused on platforms with native preadv
+pub(crate) fn read_vectored_fallback(
+    mut offset: u64,
+    bufs: &mut [IoSliceMut<'_>],
+    read_at: impl Fn(u64, &mut [u8]) -> Result<usize, HandleIoError>,
+) -> Result<usize, HandleIoError> {
+    let mut total = 0;
+    for buf in bufs.iter_mut() {
+        let read = match read_at(offset, buf) {
+            Ok(read) => read,
+            // Report what was transferred; the next call surfaces the error.
+            Err(_) if total > 0 => break,
+            Err(e) => return Err(e),
+        };
+        total += read;
+        offset += read as u64;
+        if read < buf.len() {
+            break;
+        }
+    }
+
+    Ok(total)
+}
+
+/// Emulates a vectored write with one positional write per buffer.
+///
+/// Stops at the first short write, so the buffers are drained in order as with `pwritev`.
+/// An error after some bytes were written ends the write early, returning those bytes.
+#[allow(dead_code)] // Unused on platforms with native pwritev
+pub(crate) fn write_vectored_fallback(
+    mut offset: u64,
+    bufs: &[IoSlice<'_>],
+    write_at: impl Fn(u64, &[u8]) -> Result<usize, HandleIoError>,
+) -> Result<usize, HandleIoError> {
+    let mut total = 0;
+    for buf in bufs.iter() {
+        let written = match write_at(offset, buf) {
+            Ok(written) => written,
+            // Report what was transferred; the next call surfaces the error.
+            Err(_) if total > 0 => break,
+            Err(e) => return Err(e),
+        };
+        total += written;
+        offset += written as u64;
+        if written < buf.len() {
+            break;
+        }
+    }
+
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn per_buffer_splits_total_in_order() {
+        let (a, b, c) = ([0u8; 4], [0u8; 6], [0u8; 8]);
+        let bufs = [IoSlice::new(&a), IoSlice::new(&b), IoSlice::new(&c)];
+
+        let transfer = VectoredTransfer::new(7);
+        let counts: std::vec::Vec<usize> = transfer.per_buffer(&bufs).collect();
+        assert_eq!(counts, [4, 3, 0]);
+    }
+
+    #[test]
+    fn fallback_read_stops_at_short_read() {
+        let data = b"Hello, World!";
+        let read_at = |offset: u64, buf: &mut [u8]| {
+            let start = (offset as usize).min(data.len());
+            let len = buf.len().min(data.len() - start);
+            buf[..len].copy_from_slice(&data[start..start + len]);
+            Ok(len)
+        };
+
+        let (mut a, mut b, mut c) = ([0u8; 7], [0u8; 10], [0u8; 4]);
+        let mut bufs = [
+            IoSliceMut::new(&mut a),
+            IoSliceMut::new(&mut b),
+            IoSliceMut::new(&mut c),
+        ];
+        assert_eq!(read_vectored_fallback(0, &mut bufs, read_at).unwrap(), 13);
+        assert_eq!(&a, b"Hello, ");
+        assert_eq!(&b[..6], b"World!");
+        assert_eq!(c, [0u8; 4]);
+    }
+
+    #[test]
+    fn fallback_returns_partial_transfer_before_error() {
+        let (a, b) = ([1u8; 4], [2u8; 4]);
+        let bufs = [IoSlice::new(&a), IoSlice::new(&b)];
+
+        let fail_after_first = |offset: u64, buf: &[u8]| match offset {
+            0 => Ok(buf.len()),
+            _ => Err(HandleIoError::FailedToWrite(5)),
+        };
+        assert!(matches!(
+            write_vectored_fallback(0, &bufs, fail_after_first),
+            Ok(4)
+        ));
+        assert!(matches!(
+            write_vectored_fallback(4, &bufs, fail_after_first),
+            Err(HandleIoError::FailedToWrite(5))
+        ));
+
+        let (mut c, mut d) = ([0u8; 4], [0u8; 4]);
+        let mut bufs = [IoSliceMut::new(&mut c), IoSliceMut::new(&mut d)];
+        let read = read_vectored_fallback(0, &mut bufs, |offset, buf: &mut [u8]| match offset {
+            0 => Ok(buf.len()),
+            _ => Err(HandleIoError::FailedToRead(5)),
+        });
+        assert!(matches!(read, Ok(4)));
+    }
+}
@@ -1,8 +1,13 @@
-use super::HandleOpenError;
+use super::{FileMetadata, FileTimestamp, HandleIoError, HandleOpenError, LockKind};
 #[cfg(not(feature = "std"))]
 use crate::util::to_wide;
-use core::{ffi::c_void, ptr::*};
-use windows_sys::Win32::{Foundation::*, Storage::FileSystem::*};
+use alloc::vec::Vec;
+use core::{
+    ffi::c_void,
+    mem::{size_of, zeroed},
+    ptr::*,
+};
+use windows_sys::Win32::{Foundation::*, Storage::FileSystem::*, System::IO::OVERLAPPED};
 
 /// Opens the file with specified access and shared permissions.
 ///
@@ -92,6 +97,130 @@ pub(crate) fn open_with_access(
     Ok(handle)
 }
 
+/// Creates a new file named `path` + `suffix` + a unique hexadecimal string, for reading and
+/// writing, with `CREATE_NEW`.
+///
+/// # Arguments
+///
+/// * `path` - The path to derive the name from, as UTF-16 without a NUL terminator.
+/// * `suffix` - Appended to `path` before the unique string (e.g. `.tmp`).
+///
+/// # Returns
+///
+/// The handle and the NUL-terminated path of the new file.
+///
+/// # Errors
+///
+/// Returns a `HandleOpenError` if the file cannot be created.
+pub(crate) fn create_unique(
+    path: &[u16],
+    suffix: &str,
+) -> Result<(HANDLE, Vec<u16>), HandleOpenError> {
+    use core::sync::atomic::{AtomicU32, Ordering};
+    use windows_sys::Win32::System::Threading::GetCurrentProcessId;
+
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+    let process = unsafe { GetCurrentProcessId() };
+
+    loop {
+        let mut temp_path = Vec::with_capacity(path.len() + suffix.len() + 17);
+        temp_path.extend_from_slice(path);
+        temp_path.extend(suffix.encode_utf16());
+        for value in [process, COUNTER.fetch_add(1, Ordering::Relaxed)] {
+            for shift in (0..32).step_by(4).rev() {
+                temp_path.push(b"0123456789abcdef"[(value >> shift) as usize & 0xF] as u16);
+            }
+        }
+        temp_path.push(0);
+
+        let handle = unsafe {
+            CreateFileW(
+                temp_path.as_ptr(),
+                GENERIC_READ | GENERIC_WRITE,
+                FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE,
+                null_mut(),
+                CREATE_NEW,
+                FILE_ATTRIBUTE_NORMAL,
+                null_mut(),
+            )
+        };
+
+        if handle != INVALID_HANDLE_VALUE {
+            return Ok((handle, temp_path));
+        }
+
+        // Left behind by another process which reused our ID; try the next name.
+        let error_code = unsafe { GetLastError() };
+        if error_code != ERROR_FILE_EXISTS {
+            return Err(HandleOpenError::failed_to_open_file_handle_wide(
+                error_code, path,
+            ));
+        }
+    }
+}
+
+/// Renames the file at `from` over `to`, replacing it if it exists, and waits until the
+/// rename is flushed to disk (`MOVEFILE_WRITE_THROUGH`).
+///
+/// Both paths must be NUL-terminated.
+pub(crate) fn replace_file(from: &[u16], to: &[u16]) -> Result<(), HandleIoError> {
+    let flags = MOVEFILE_REPLACE_EXISTING | MOVEFILE_WRITE_THROUGH;
+    if unsafe { MoveFileExW(from.as_ptr(), to.as_ptr(), flags) } == 0 {
+        return Err(HandleIoError::FailedToRename(unsafe { GetLastError() }));
+    }
+
+    Ok(())
+}
+
+/// Deletes the file at the NUL-terminated `path`. If the file is still open, it is removed
+/// once the last handle to it is closed.
+pub(crate) fn delete_file(path: &[u16]) {
+    unsafe {
+        DeleteFileW(path.as_ptr());
+    }
+}
+
+/// Duplicates the file handle within the current process.
+///
+/// # Arguments
+///
+/// * `handle` - The handle to duplicate.
+/// * `access` - Access rights for the new handle, or `None` to keep the same access.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub(crate) fn duplicate_handle(
+    handle: HANDLE,
+    access: Option<u32>,
+) -> Result<HANDLE, HandleOpenError> {
+    use windows_sys::Win32::System::Threading::GetCurrentProcess;
+
+    let (access, options) = match access {
+        Some(access) => (access, 0),
+        None => (0, DUPLICATE_SAME_ACCESS),
+    };
+
+    let mut new_handle: HANDLE = null_mut();
+    let ret = unsafe {
+        let process = GetCurrentProcess();
+        DuplicateHandle(
+            process,
+            handle,
+            process,
+            &mut new_handle,
+            access,
+            0,
+            options,
+        )
+    };
+
+    if ret == 0 {
+        return Err(HandleOpenError::FailedToDuplicateHandle(unsafe {
+            GetLastError()
+        }));
+    }
+
+    Ok(new_handle)
+}
+
 #[cfg(target_os = "windows")]
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub fn get_file_size(handle: HANDLE) -> Result<i64, HandleOpenError> {
@@ -128,3 +257,191 @@ pub(crate) fn set_file_size(handle: HANDLE, size: i64) -> Result<(), HandleOpenE
     }
     Ok(())
 }
+
+/// Creates an [`OVERLAPPED`] structure pointing at the given file offset.
+fn overlapped_at(offset: u64) -> OVERLAPPED {
+    let mut overlapped: OVERLAPPED = unsafe { zeroed() };
+    overlapped.Anonymous.Anonymous.Offset = offset as u32;
+    overlapped.Anonymous.Anonymous.OffsetHigh = (offset >> 32) as u32;
+    overlapped
+}
+
+/// Reads from the file at the given offset.
+///
+/// May read fewer bytes than requested; a return value of 0 indicates the end of the file.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub(crate) fn read_at(handle: HANDLE, offset: u64, buf: &mut [u8]) -> Result<usize, HandleIoError> {
+    let len = buf.len().min(u32::MAX as usize) as u32;
+    let mut overlapped = overlapped_at(offset);
+    let mut read = 0u32;
+
+    let ret = unsafe { ReadFile(handle, buf.as_mut_ptr(), len, &mut read, &mut overlapped) };
+    if ret == 0 {
+        let error_code = unsafe { GetLastError() };
+        if error_code == ERROR_HANDLE_EOF {
+            return Ok(0);
+        }
+
+        return Err(HandleIoError::FailedToRead(error_code));
+    }
+
+    Ok(read as usize)
+}
+
+/// Writes to the file at the given offset.
+///
+/// May write fewer bytes than requested.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub(crate) fn write_at(handle: HANDLE, offset: u64, buf: &[u8]) -> Result<usize, HandleIoError> {
+    let len = buf.len().min(u32::MAX as usize) as u32;
+    let mut overlapped = overlapped_at(offset);
+    let mut written = 0u32;
+
+    let ret = unsafe { WriteFile(handle, buf.as_ptr(), len, &mut written, &mut overlapped) };
+    if ret == 0 {
+        return Err(HandleIoError::FailedToWrite(unsafe { GetLastError() }));
+    }
+
+    Ok(written as usize)
+}
+
+/// Reads from the file at the given offset into multiple buffers, in order.
+///
+/// Windows has no positional scatter read for buffered handles, so this issues
+/// one read per buffer.
+#[cfg(feature = "std")]
+pub(crate) fn read_vectored_at(
+    handle: HANDLE,
+    offset: u64,
+    bufs: &mut [std::io::IoSliceMut<'_>],
+) -> Result<usize, HandleIoError> {
+    super::vectored::read_vectored_fallback(offset, bufs, |offset, buf| {
+        read_at(handle, offset, buf)
+    })
+}
+
+/// Writes multiple buffers, in order, to the file at the given offset.
+///
+/// Windows has no positional gather write for buffered handles, so this issues
+/// one write per buffer.
+#[cfg(feature = "std")]
+pub(crate) fn write_vectored_at(
+    handle: HANDLE,
+    offset: u64,
+    bufs: &[std::io::IoSlice<'_>],
+) -> Result<usize, HandleIoError> {
+    super::vectored::write_vectored_fallback(offset, bufs, |offset, buf| {
+        write_at(handle, offset, buf)
+    })
+}
+
+/// Flushes all buffered data and metadata of the file to the storage device.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub(crate) fn sync_all(handle: HANDLE) -> Result<(), HandleIoError> {
+    if unsafe { FlushFileBuffers(handle) } == 0 {
+        return Err(HandleIoError::FailedToSync(unsafe { GetLastError() }));
+    }
+
+    Ok(())
+}
+
+/// Takes (`Some(kind)`) or releases (`None`) a lock on a byte range of the file.
+///
+/// A `len` of 0 covers the range from `offset` onwards, however large the file grows.
+///
+/// # Errors
+///
+/// Returns [`HandleIoError::WouldBlock`] if `blocking` is false and the range is locked
+/// elsewhere, or another `HandleIoError` if locking fails.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub(crate) fn lock_range(
+    handle: HANDLE,
+    offset: u64,
+    len: u64,
+    kind: Option<LockKind>,
+    blocking: bool,
+) -> Result<(), HandleIoError> {
+    let len = if len == 0 { u64::MAX } else { len };
+    let (len_low, len_high) = (len as u32, (len >> 32) as u32);
+    let mut overlapped = overlapped_at(offset);
+
+    let result = match kind {
+        Some(kind) => {
+            let mut flags = 0;
+            if kind == LockKind::Exclusive {
+                flags |= LOCKFILE_EXCLUSIVE_LOCK;
+            }
+            if !blocking {
+                flags |= LOCKFILE_FAIL_IMMEDIATELY;
+            }
+            unsafe { LockFileEx(handle, flags, 0, len_low, len_high, &mut overlapped) }
+        }
+        None => unsafe { UnlockFileEx(handle, 0, len_low, len_high, &mut overlapped) },
+    };
+
+    if result == 0 {
+        return Err(match unsafe { GetLastError() } {
+            ERROR_LOCK_VIOLATION | ERROR_IO_PENDING => HandleIoError::WouldBlock,
+            code => HandleIoError::FailedToLock(code),
+        });
+    }
+
+    Ok(())
+}
+
+/// Number of 100ns intervals between the `FILETIME` epoch (1601-01-01) and the Unix epoch.
+const FILETIME_UNIX_EPOCH: i64 = 116_444_736_000_000_000;
+
+fn filetime_to_timestamp(time: i64) -> FileTimestamp {
+    let since_epoch = time - FILETIME_UNIX_EPOCH;
+    FileTimestamp {
+        secs: since_epoch.div_euclid(10_000_000),
+        nanos: (since_epoch.rem_euclid(10_000_000) * 100) as u32,
+    }
+}
+
+/// Gets the metadata of the file.
+///
+/// # Errors
+///
+/// Returns a `HandleIoError` if the metadata cannot be queried.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub(crate) fn get_metadata(handle: HANDLE) -> Result<FileMetadata, HandleIoError> {
+    let mut info: BY_HANDLE_FILE_INFORMATION = unsafe { zeroed() };
+    let mut basic: FILE_BASIC_INFO = unsafe { zeroed() };
+    let mut standard: FILE_STANDARD_INFO = unsafe { zeroed() };
+
+    let succeeded = unsafe {
+        GetFileInformationByHandle(handle, &mut info) != 0
+            && GetFileInformationByHandleEx(
+                handle,
+                FileBasicInfo,
+                &mut basic as *mut FILE_BASIC_INFO as *mut c_void,
+                size_of::<FILE_BASIC_INFO>() as u32,
+            ) != 0
+            && GetFileInformationByHandleEx(
+                handle,
+                FileStandardInfo,
+                &mut standard as *mut FILE_STANDARD_INFO as *mut c_void,
+                size_of::<FILE_STANDARD_INFO>() as u32,
+            ) != 0
+    };
+
+    if !succeeded {
+        return Err(HandleIoError::FailedToGetMetadata(unsafe {
+            GetLastError()
+        }));
+    }
+
+    Ok(FileMetadata {
+        size: standard.EndOfFile as u64,
+        blocks: standard.AllocationSize as u64 / 512,
+        block_size: 0,
+        mode: basic.FileAttributes,
+        device: info.dwVolumeSerialNumber as u64,
+        inode: ((info.nFileIndexHigh as u64) << 32) | info.nFileIndexLow as u64,
+        modified: filetime_to_timestamp(basic.LastWriteTime),
+        changed: filetime_to_timestamp(basic.ChangeTime),
+        created: Some(filetime_to_timestamp(basic.CreationTime)),
+    })
+}
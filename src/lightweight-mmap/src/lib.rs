@@ -15,8 +15,19 @@ pub(crate) mod util;
 extern crate alloc;
 
 // Re-export the main types at the crate root for convenience
-pub use handles::{HandleOpenError, ReadOnlyFileHandle, ReadWriteFileHandle};
+#[cfg(unix)]
+pub use handles::DirHandle;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use handles::PhysicalExtent;
+pub use handles::{
+    copy_range, AtomicFileWriter, CopyOutcome, CopyStrategy, Extent, ExtentKind, Extents,
+    FileAdvice, FileHandleOptions, FileLockGuard, FileMetadata, FileTimestamp, HandleIoError,
+    HandleOpenError, LockKind, ReadOnlyFileHandle, ReadWriteFileHandle, SyncRangeFlags,
+};
+#[cfg(feature = "std")]
+pub use handles::{ReadOnlyFileCursor, ReadWriteFileCursor, VectoredTransfer};
 #[cfg(feature = "mmap")]
 pub use mmap::{
-    MemoryAdvice, MmapError, OwnedReadOnlyMmap, OwnedReadWriteMmap, ReadOnlyMmap, ReadWriteMmap,
+    AnonMmap, CowMmap, ExecutableMmap, MemoryAdvice, MmapError, OwnedReadOnlyMmap,
+    OwnedReadWriteMmap, Protection, ReadOnlyMmap, ReadWriteMmap,
 };
@@ -0,0 +1,275 @@
+use super::*;
+use core::{
+    ffi::c_void,
+    slice::{from_raw_parts, from_raw_parts_mut},
+};
+
+#[cfg(unix)]
+mod unix;
+#[cfg(target_os = "windows")]
+mod windows;
+
+#[cfg(unix)]
+use unix::*;
+#[cfg(target_os = "windows")]
+use windows::*;
+
+/// A memory mapping which is not backed by a file, i.e. zero initialized memory
+/// allocated directly from the operating system.
+///
+/// Useful for large scratch buffers, whose pages can be handed back to the operating
+/// system with [`AnonMmap::discard`] while keeping the mapping itself.
+///
+/// Uses `MAP_ANONYMOUS` on Unix. On Windows, private mappings use `VirtualAlloc`, and
+/// shared mappings use a section backed by the page file.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AnonMmap {
+    inner: AnonMmapInner,
+    length: usize,
+}
+
+// SAFETY: The mapping is owned memory, not tied to the thread which created it.
+unsafe impl Send for AnonMmap {}
+unsafe impl Sync for AnonMmap {}
+
+impl AnonMmap {
+    /// Creates a new private anonymous mapping of `len` zeroed bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - The length of the mapping in bytes
+    ///
+    /// # Errors
+    ///
+    /// Returns a `MmapError` if the system cannot allocate the required memory.
+    pub fn new(len: usize) -> Result<Self, MmapError> {
+        Ok(AnonMmap {
+            inner: AnonMmapInner::new(len, false)?,
+            length: len,
+        })
+    }
+
+    /// Creates a new shared anonymous mapping of `len` zeroed bytes.
+    ///
+    /// On Unix, the memory stays shared with child processes created by `fork`, so changes made
+    /// by either side are seen by the other. (A private mapping is copied on write instead.)
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - The length of the mapping in bytes
+    ///
+    /// # Errors
+    ///
+    /// Returns a `MmapError` if the system cannot allocate the required memory.
+    pub fn new_shared(len: usize) -> Result<Self, MmapError> {
+        Ok(AnonMmap {
+            inner: AnonMmapInner::new(len, true)?,
+            length: len,
+        })
+    }
+
+    /// Returns a slice of the mapped memory.
+    #[inline]
+    pub fn as_slice(&self) -> &[u8] {
+        unsafe { from_raw_parts(self.data(), self.len()) }
+    }
+
+    /// Returns a mutable slice of the mapped memory.
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { from_raw_parts_mut(self.data(), self.len()) }
+    }
+
+    /// Returns a raw pointer to the mapped memory.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the memory is accessed within the bounds of
+    /// the mapping. The caller must also ensure the pointer does not outlast the
+    /// lifetime of the mapping. It is recommended you use [`Self::as_slice`] or
+    /// [`Self::as_mut_slice`] instead for compiler enforced safety.
+    #[inline]
+    pub fn data(&self) -> *mut u8 {
+        self.inner.data() as *mut u8
+    }
+
+    /// Returns the length of the mapped region in bytes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    /// Returns whether the mapping is empty (zero length).
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Provides advice to the operating system about how the memory mapping will be accessed.
+    ///
+    /// # Arguments
+    ///
+    /// * `advice` - Bit flags indicating the expected access patterns for this memory region
+    ///
+    /// # Note
+    ///
+    /// This is a hint to the operating system and may be ignored. Not all advice types
+    /// are supported on all platforms. On Windows, only [`MemoryAdvice::WILL_NEED`] has an effect.
+    /// Multiple advice flags can be combined using bitwise operations.
+    pub fn advise(&self, advice: MemoryAdvice) {
+        if !self.is_empty() {
+            advise_memory(self.inner.data(), self.length, advice)
+        }
+    }
+
+    /// Hands the memory behind the mapping back to the operating system, keeping the
+    /// mapping itself, e.g. once a scratch buffer is no longer needed.
+    ///
+    /// The contents of a private mapping are lost: on Linux and Android, it reads back as
+    /// zeros afterwards; elsewhere, its contents are unspecified until written again.
+    /// Shared mappings keep their contents.
+    ///
+    /// Uses `madvise` with `MADV_DONTNEED` on Unix and `DiscardVirtualMemory` on Windows.
+    /// This is a hint, and may have no effect.
+    pub fn discard(&mut self) {
+        if !self.is_empty() {
+            discard_memory(self.inner.data(), self.length)
+        }
+    }
+
+    /// Changes the access allowed to part of the mapping.
+    ///
+    /// Protection is changed a whole page at a time, so the range must start on a page
+    /// boundary (i.e. `data() + offset` is a multiple of the page size), and end on one too,
+    /// unless it extends to the end of the mapping.
+    ///
+    /// Uses `mprotect` on Unix and `VirtualProtect` on Windows.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The offset of the range from the start of the mapping.
+    /// * `len` - The length of the range in bytes.
+    /// * `protection` - The access to allow. [`Protection::empty`] makes any access fault.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`MmapError`] if the range is outside of the mapping or not aligned to page
+    /// boundaries, or the protection cannot be changed.
+    ///
+    /// # Safety
+    ///
+    /// Any access to a page which no longer allows it faults the process (`SIGSEGV` on Unix,
+    /// an access violation on Windows). The caller must ensure that nothing, including slices
+    /// previously returned by [`Self::as_slice`] and [`Self::as_mut_slice`], accesses the
+    /// range in a way it no longer allows.
+    pub unsafe fn protect(
+        &self,
+        offset: usize,
+        len: usize,
+        protection: Protection,
+    ) -> Result<(), MmapError> {
+        if offset.checked_add(len).is_none_or(|end| end > self.len()) {
+            return Err(MmapError::mapping_failed("Protect range is out of bounds"));
+        }
+
+        if len == 0 {
+            return Ok(());
+        }
+
+        let len = whole_page_range(offset, len, self.len())?;
+        protect_memory(self.data().add(offset) as *mut c_void, len, protection)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_create_empty_mapping() {
+        let mapping = AnonMmap::new(0).unwrap();
+        assert!(mapping.is_empty());
+        assert!(mapping.as_slice().is_empty());
+    }
+
+    #[test]
+    fn mapping_is_zeroed_and_writable() {
+        for mut mapping in [
+            AnonMmap::new(3 * 4096 + 5).unwrap(),
+            AnonMmap::new_shared(3 * 4096 + 5).unwrap(),
+        ] {
+            assert_eq!(mapping.len(), 3 * 4096 + 5);
+            assert!(mapping.as_slice().iter().all(|&x| x == 0));
+
+            mapping.as_mut_slice().fill(0xAA);
+            assert!(mapping.as_slice().iter().all(|&x| x == 0xAA));
+        }
+    }
+
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn discard_zeroes_private_mapping() {
+        let mut mapping = AnonMmap::new(16 * 4096).unwrap();
+        mapping.as_mut_slice().fill(0xAA);
+
+        mapping.discard();
+        assert!(mapping.as_slice().iter().all(|&x| x == 0));
+    }
+
+    #[test]
+    fn can_protect_mapping() {
+        let page = crate::util::get_page_size() as usize;
+        let mut mapping = AnonMmap::new(3 * page).unwrap();
+        mapping.as_mut_slice().fill(0xAA);
+
+        unsafe {
+            mapping.protect(page, page, Protection::empty()).unwrap();
+            assert!(mapping.protect(page, 3 * page, Protection::READ).is_err());
+            assert!(mapping.protect(1, page - 1, Protection::READ).is_err());
+            assert!(mapping.protect(0, page + 1, Protection::READ).is_err());
+            assert!(mapping.as_slice()[..page].iter().all(|&x| x == 0xAA));
+            mapping
+                .protect(0, 3 * page, Protection::READ | Protection::WRITE)
+                .unwrap();
+        }
+        mapping.as_mut_slice()[page] = 1;
+        assert_eq!(mapping.as_slice()[page], 1);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn reading_inaccessible_pages_faults() {
+        let mapping = AnonMmap::new(4096).unwrap();
+        unsafe { mapping.protect(0, 4096, Protection::empty()).unwrap() };
+
+        match unsafe { libc::fork() } {
+            0 => {
+                unsafe { mapping.data().read_volatile() };
+                unsafe { libc::_exit(0) };
+            }
+            pid => {
+                let mut status = 0;
+                unsafe { libc::waitpid(pid, &mut status, 0) };
+                assert!(libc::WIFSIGNALED(status));
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn shared_mapping_is_visible_across_fork() {
+        let mut mapping = AnonMmap::new_shared(4096).unwrap();
+
+        match unsafe { libc::fork() } {
+            0 => {
+                mapping.as_mut_slice()[0] = 42;
+                unsafe { libc::_exit(0) };
+            }
+            pid => {
+                let mut status = 0;
+                unsafe { libc::waitpid(pid, &mut status, 0) };
+                assert_eq!(mapping.as_slice()[0], 42);
+            }
+        }
+    }
+}
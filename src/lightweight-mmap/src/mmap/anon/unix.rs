@@ -0,0 +1,34 @@
+use super::*;
+use libc::*;
+use unix_common::create_anonymous_mmap;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct AnonMmapInner {
+    ptr: *mut c_void,
+    length: usize,
+}
+
+impl AnonMmapInner {
+    pub(crate) fn new(len: usize, shared: bool) -> Result<Self, MmapError> {
+        let flags = if shared { MAP_SHARED } else { MAP_PRIVATE };
+        Ok(AnonMmapInner {
+            ptr: create_anonymous_mmap(len, flags)?,
+            length: len,
+        })
+    }
+
+    #[inline]
+    pub fn data(&self) -> *mut c_void {
+        self.ptr
+    }
+}
+
+impl Drop for AnonMmapInner {
+    fn drop(&mut self) {
+        unsafe {
+            if self.length != 0 {
+                munmap(self.ptr, self.length);
+            }
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use super::*;
+use core::ffi::c_void;
+use windows_sys::Win32::{Foundation::*, System::Memory::*};
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct AnonMmapInner {
+    ptr: *mut c_void,
+    /// Section backing a shared mapping; null for private memory from `VirtualAlloc`.
+    mapping: HANDLE,
+    length: usize,
+}
+
+impl AnonMmapInner {
+    pub(crate) fn new(len: usize, shared: bool) -> Result<Self, MmapError> {
+        let (ptr, mapping) = create_anonymous_view(len, shared)?;
+        Ok(AnonMmapInner {
+            ptr,
+            mapping,
+            length: len,
+        })
+    }
+
+    #[inline]
+    pub fn data(&self) -> *mut c_void {
+        self.ptr
+    }
+}
+
+impl Drop for AnonMmapInner {
+    fn drop(&mut self) {
+        if self.length == 0 {
+            return;
+        }
+
+        unsafe {
+            if self.mapping.is_null() {
+                VirtualFree(self.ptr, 0, MEM_RELEASE);
+            } else {
+                UnmapViewOfFile(MEMORY_MAPPED_VIEW_ADDRESS { Value: self.ptr });
+                CloseHandle(self.mapping);
+            }
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use super::*;
+use crate::handles::ReadOnlyFileHandle;
+use core::slice::{from_raw_parts, from_raw_parts_mut};
+
+#[cfg(unix)]
+mod unix;
+#[cfg(target_os = "windows")]
+mod windows;
+
+#[cfg(unix)]
+use unix::*;
+#[cfg(target_os = "windows")]
+use windows::*;
+
+/// A private, copy-on-write memory mapping of a file's contents.
+///
+/// The mapping starts out with the contents of the file, and can be written to like
+/// any other memory. Written pages are copied on first write, so changes stay private
+/// to this mapping: they are never written back to the file, and are not seen by other
+/// mappings or processes. This only needs a [`ReadOnlyFileHandle`], so it works on files
+/// without write permission.
+///
+/// Uses `MAP_PRIVATE` on Unix and `FILE_MAP_COPY` on Windows.
+///
+/// Pages which have not been written to yet may reflect later changes made to the file
+/// by others; avoid modifying the underlying file while it is mapped.
+///
+/// The mapping cannot outlive the file handle it was created from.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CowMmap<'a> {
+    inner: CowMmapInner<'a>,
+    offset_adjustment: usize,
+    length: usize,
+}
+
+impl<'a> CowMmap<'a> {
+    /// Creates a new copy-on-write memory mapping for the specified file handle.
+    ///
+    /// # Arguments
+    ///
+    /// * `handle` - The file handle to create the mapping from
+    /// * `offset` - The offset into the file where the mapping should begin
+    /// * `len` - The length of the mapping in bytes
+    ///
+    /// # Errors
+    ///
+    /// Returns a `MmapError` if:
+    /// - The mapping cannot be created
+    /// - The offset and length would exceed the file size
+    /// - The system cannot allocate the required resources
+    pub fn new(handle: &'a ReadOnlyFileHandle, offset: u64, len: usize) -> Result<Self, MmapError> {
+        #[cfg(feature = "trim-file-lengths")]
+        let len = adjust_len_to_file_size(handle.size(), offset, len)?;
+
+        let (inner, offset_adjustment, length) = CowMmapInner::new(handle, offset, len)?;
+        Ok(CowMmap {
+            inner,
+            offset_adjustment,
+            length,
+        })
+    }
+
+    /// Returns a slice of the mapped memory.
+    #[inline]
+    pub fn as_slice(&self) -> &[u8] {
+        unsafe { from_raw_parts(self.data(), self.len()) }
+    }
+
+    /// Returns a mutable slice of the mapped memory.
+    ///
+    /// Writes through this slice only change this mapping, never the file.
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { from_raw_parts_mut(self.data(), self.len()) }
+    }
+
+    /// Returns a raw pointer to the mapped memory.
+    ///
+    /// The returned pointer is adjusted for the requested offset, accounting for
+    /// any page alignment requirements.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the memory is accessed within the bounds of
+    /// the mapping. The caller must also ensure the pointer does not outlast the
+    /// lifetime of the mapping. It is recommended you use [`Self::as_slice`] or
+    /// [`Self::as_mut_slice`] instead for compiler enforced safety.
+    #[inline]
+    pub fn data(&self) -> *mut u8 {
+        unsafe { (self.inner.data() as *mut u8).add(self.offset_adjustment) }
+    }
+
+    /// Returns the length of the mapped region in bytes.
+    ///
+    /// This returns the originally requested length, not including any padding
+    /// added for page alignment.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.length - self.offset_adjustment
+    }
+
+    /// Returns whether the mapping is empty (zero length).
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Provides advice to the operating system about how the memory mapping will be accessed.
+    ///
+    /// # Arguments
+    ///
+    /// * `advice` - Bit flags indicating the expected access patterns for this memory region
+    ///
+    /// # Note
+    ///
+    /// This is a hint to the operating system and may be ignored. Not all advice types
+    /// are supported on all platforms. On Windows, only [`MemoryAdvice::WILL_NEED`] has an effect.
+    /// Multiple advice flags can be combined using bitwise operations.
+    pub fn advise(&self, advice: MemoryAdvice) {
+        if !self.is_empty() {
+            advise_memory(self.inner.data(), self.length, advice)
+        }
+    }
+
+    /// Drops the private copies of written pages, handing their memory back to the
+    /// operating system.
+    ///
+    /// Changes made to the mapping are lost: on Linux and Android, it reverts to the file's
+    /// contents; elsewhere, changed pages are unspecified until written again.
+    ///
+    /// Uses `madvise` with `MADV_DONTNEED` on Unix. This is a hint, and may have no effect;
+    /// it has none on Windows.
+    pub fn discard(&mut self) {
+        if !self.is_empty() {
+            discard_memory(self.inner.data(), self.length)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn can_create_empty_mapping() {
+        let file = NamedTempFile::new().unwrap();
+        let handle = ReadOnlyFileHandle::open(file.path()).unwrap();
+
+        let mapping = CowMmap::new(&handle, 0, 0).unwrap();
+        assert!(mapping.is_empty());
+    }
+
+    #[test]
+    fn writes_do_not_reach_file() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"Hello, World!").unwrap();
+        file.flush().unwrap();
+
+        let handle = ReadOnlyFileHandle::open(file.path()).unwrap();
+        let mut mapping = CowMmap::new(&handle, 7, 5).unwrap();
+        assert_eq!(mapping.as_slice(), b"World");
+
+        mapping.as_mut_slice().copy_from_slice(b"Patch");
+        assert_eq!(mapping.as_slice(), b"Patch");
+
+        // Neither the file, nor other mappings of it, see the change.
+        let shared = ReadOnlyMmap::new(&handle, 0, 13).unwrap();
+        assert_eq!(shared.as_slice(), b"Hello, World!");
+        drop(mapping);
+        assert_eq!(std::fs::read(file.path()).unwrap(), b"Hello, World!");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn works_without_write_permission() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"Hello, World!").unwrap();
+        file.flush().unwrap();
+        std::fs::set_permissions(file.path(), std::fs::Permissions::from_mode(0o444)).unwrap();
+
+        let handle = ReadOnlyFileHandle::open(file.path()).unwrap();
+        let mut mapping = CowMmap::new(&handle, 0, 13).unwrap();
+        mapping.as_mut_slice()[..5].copy_from_slice(b"Howdy");
+        assert_eq!(mapping.as_slice(), b"Howdy, World!");
+    }
+
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn discard_reverts_to_file_contents() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"Hello, World!").unwrap();
+        file.flush().unwrap();
+
+        let handle = ReadOnlyFileHandle::open(file.path()).unwrap();
+        let mut mapping = CowMmap::new(&handle, 0, 13).unwrap();
+        mapping.as_mut_slice()[..5].copy_from_slice(b"Howdy");
+
+        mapping.discard();
+        assert_eq!(mapping.as_slice(), b"Hello, World!");
+    }
+}
@@ -0,0 +1,54 @@
+use super::*;
+use core::marker::PhantomData;
+use libc::*;
+use unix_common::create_mmap;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct CowMmapInner<'a> {
+    ptr: *mut c_void,
+    length: usize,
+    _phantom: PhantomData<&'a ReadOnlyFileHandle>,
+}
+
+impl<'a> CowMmapInner<'a> {
+    pub(crate) fn new(
+        handle: &'a ReadOnlyFileHandle,
+        offset: u64,
+        len: usize,
+    ) -> Result<(Self, usize, usize), MmapError> {
+        // MAP_PRIVATE pages start out shared with the page cache, and are copied on first write,
+        // so PROT_WRITE is allowed even though the file is only open for reading.
+        let (ptr, offset_adjustment, adjusted_len) = create_mmap(
+            handle.handle().fd(),
+            offset,
+            len,
+            PROT_READ | PROT_WRITE,
+            MAP_PRIVATE,
+        )?;
+
+        Ok((
+            CowMmapInner {
+                ptr,
+                length: adjusted_len,
+                _phantom: PhantomData,
+            },
+            offset_adjustment,
+            adjusted_len,
+        ))
+    }
+
+    #[inline]
+    pub fn data(&self) -> *mut c_void {
+        self.ptr
+    }
+}
+
+impl Drop for CowMmapInner<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.ptr.is_null() {
+                munmap(self.ptr, self.length);
+            }
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use super::*;
+use core::{ffi::c_void, marker::PhantomData};
+use windows_sys::Win32::System::Memory::*;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct CowMmapInner<'a> {
+    ptr: *mut c_void,
+    _phantom: PhantomData<&'a ReadOnlyFileHandle>,
+}
+
+impl<'a> CowMmapInner<'a> {
+    pub(crate) fn new(
+        handle: &'a ReadOnlyFileHandle,
+        offset: u64,
+        len: usize,
+    ) -> Result<(Self, usize, usize), MmapError> {
+        // FILE_MAP_COPY views of a PAGE_READONLY mapping are copy-on-write, so the
+        // mapping object cached on the handle can be shared with read-only views.
+        let inner = handle.handle();
+        let (ptr, offset_adjustment, adjusted_len) = create_view(
+            inner.handle(),
+            unsafe { &mut *inner.mapping.get() },
+            offset,
+            len,
+            PAGE_READONLY,
+            FILE_MAP_COPY,
+        )?;
+
+        Ok((
+            CowMmapInner {
+                ptr,
+                _phantom: PhantomData,
+            },
+            offset_adjustment,
+            adjusted_len,
+        ))
+    }
+
+    #[inline]
+    pub fn data(&self) -> *mut c_void {
+        self.ptr
+    }
+}
+
+impl Drop for CowMmapInner<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.ptr.is_null() {
+                UnmapViewOfFile(MEMORY_MAPPED_VIEW_ADDRESS { Value: self.ptr });
+            }
+        }
+    }
+}
@@ -28,6 +28,38 @@ pub enum MmapError {
     )]
     FailedToMapMemory(i32),
 
+    /// Failed to flush the mapping to the file on Windows.
+    #[cfg(target_os = "windows")]
+    #[cfg_attr(
+        not(feature = "no-format"),
+        error("Failed to flush mapping on Windows. Error code: {0}")
+    )]
+    FailedToFlush(u32),
+
+    /// Failed to flush the mapping to the file on Unix.
+    #[cfg(unix)]
+    #[cfg_attr(
+        not(feature = "no-format"),
+        error("Failed to flush mapping on Unix. Error code: {0}")
+    )]
+    FailedToFlush(i32),
+
+    /// Failed to change the protection of the mapping's pages on Windows.
+    #[cfg(target_os = "windows")]
+    #[cfg_attr(
+        not(feature = "no-format"),
+        error("Failed to change page protection on Windows. Error code: {0}")
+    )]
+    FailedToProtect(u32),
+
+    /// Failed to change the protection of the mapping's pages on Unix.
+    #[cfg(unix)]
+    #[cfg_attr(
+        not(feature = "no-format"),
+        error("Failed to change page protection on Unix. Error code: {0}")
+    )]
+    FailedToProtect(i32),
+
     /// Generic mapping failure with a message.
     #[cfg_attr(
         all(not(feature = "no-format"), debug_assertions),
@@ -86,6 +118,24 @@ impl core::fmt::Display for MmapError {
                 f.write_str(&error_msg)
             }
 
+            MmapError::FailedToFlush(code) => {
+                let mut buffer = Buffer::new();
+                let code_str = buffer.format(*code);
+                let error_msg = unsafe {
+                    concat_2_no_overflow("Failed to flush mapping. Error code: ", code_str)
+                };
+                f.write_str(&error_msg)
+            }
+
+            MmapError::FailedToProtect(code) => {
+                let mut buffer = Buffer::new();
+                let code_str = buffer.format(*code);
+                let error_msg = unsafe {
+                    concat_2_no_overflow("Failed to change page protection. Error code: ", code_str)
+                };
+                f.write_str(&error_msg)
+            }
+
             MmapError::MappingFailed(msg) => {
                 let error_msg = unsafe { concat_2_no_overflow("Mapping failed: ", msg) };
                 f.write_str(&error_msg)
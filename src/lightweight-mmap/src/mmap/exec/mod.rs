@@ -0,0 +1,239 @@
+use super::*;
+use crate::handles::ReadOnlyFileHandle;
+#[cfg(target_os = "windows")]
+use core::marker::PhantomData;
+use core::{ffi::c_void, slice::from_raw_parts};
+
+/// A memory mapping whose contents can be executed as machine code, e.g. code emitted by a JIT
+/// or loaded from a file.
+///
+/// The memory is readable and executable, but never writable (W^X). To emit code, write it into
+/// an [`AnonMmap`], then flip it to executable with [`AnonMmap::into_executable`]. The
+/// instruction cache is flushed for the mapping on creation, so the CPU sees the new code.
+///
+/// A mapping created from a file cannot outlive the file handle it was created from.
+#[derive(Debug)]
+pub struct ExecutableMmap<'a> {
+    mapping: Mapping<'a>,
+}
+
+/// The memory behind an [`ExecutableMmap`].
+#[derive(Debug)]
+enum Mapping<'a> {
+    Anon(AnonMmap),
+    /// A private mapping of a file.
+    #[cfg(unix)]
+    File(CowMmap<'a>),
+    /// A copy of part of a file. A read-only handle cannot back an executable section on
+    /// Windows, so the contents are read into anonymous memory instead.
+    #[cfg(target_os = "windows")]
+    File(AnonMmap, PhantomData<&'a ReadOnlyFileHandle>),
+}
+
+// SAFETY: The memory is never written after creation, and is not tied to the thread which
+// created it.
+unsafe impl Send for ExecutableMmap<'_> {}
+unsafe impl Sync for ExecutableMmap<'_> {}
+
+impl<'a> ExecutableMmap<'a> {
+    /// Maps part of a file as executable code.
+    ///
+    /// On Unix, the file is mapped privately with `PROT_READ | PROT_EXEC`, so this fails on
+    /// filesystems mounted `noexec`. On Windows, the contents are copied into executable memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `handle` - The file handle to create the mapping from
+    /// * `offset` - The offset into the file where the mapping should begin
+    /// * `len` - The length of the mapping in bytes
+    ///
+    /// # Errors
+    ///
+    /// Returns a `MmapError` if:
+    /// - The mapping cannot be created
+    /// - The offset and length would exceed the file size
+    /// - The mapping cannot be made executable
+    pub fn new(handle: &'a ReadOnlyFileHandle, offset: u64, len: usize) -> Result<Self, MmapError> {
+        #[cfg(unix)]
+        {
+            let mapping = CowMmap::new(handle, offset, len)?;
+            make_executable(mapping.data(), mapping.len())?;
+            Ok(ExecutableMmap {
+                mapping: Mapping::File(mapping),
+            })
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            #[cfg(feature = "trim-file-lengths")]
+            let len = adjust_len_to_file_size(handle.size(), offset, len)?;
+
+            let mut copy = AnonMmap::new(len)?;
+            let buf = copy.as_mut_slice();
+            let mut read = 0;
+            while read < buf.len() {
+                match handle.read_at(offset + read as u64, &mut buf[read..]) {
+                    Ok(0) => break,
+                    Ok(n) => read += n,
+                    Err(_) => return Err(MmapError::mapping_failed("Failed to read file")),
+                }
+            }
+
+            make_executable(copy.data(), copy.len())?;
+            Ok(ExecutableMmap {
+                mapping: Mapping::File(copy, PhantomData),
+            })
+        }
+    }
+
+    /// Returns a slice of the mapped code.
+    #[inline]
+    pub fn as_slice(&self) -> &[u8] {
+        unsafe { from_raw_parts(self.data(), self.len()) }
+    }
+
+    /// Returns a raw pointer to the mapped code, e.g. to cast into a function pointer.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the memory is accessed within the bounds of the mapping,
+    /// and that the pointer does not outlast the lifetime of the mapping. Calling into the
+    /// code is only sound if it is valid machine code for the current CPU, with the calling
+    /// convention of the function pointer it is called through.
+    #[inline]
+    pub fn data(&self) -> *const u8 {
+        match &self.mapping {
+            Mapping::Anon(mapping) => mapping.data(),
+            #[cfg(unix)]
+            Mapping::File(mapping) => mapping.data(),
+            #[cfg(target_os = "windows")]
+            Mapping::File(mapping, _) => mapping.data(),
+        }
+    }
+
+    /// Returns the length of the mapped region in bytes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        match &self.mapping {
+            Mapping::Anon(mapping) => mapping.len(),
+            #[cfg(unix)]
+            Mapping::File(mapping) => mapping.len(),
+            #[cfg(target_os = "windows")]
+            Mapping::File(mapping, _) => mapping.len(),
+        }
+    }
+
+    /// Returns whether the mapping is empty (zero length).
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl AnonMmap {
+    /// Makes the whole mapping read-only and executable in place, and converts it into an
+    /// [`ExecutableMmap`].
+    ///
+    /// Write the code into the mapping first; the instruction cache is flushed as part of the
+    /// conversion, so the code can be called straight away.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`MmapError::FailedToProtect`] if the mapping cannot be made executable, e.g.
+    /// because the OS forbids executable memory in this process. The mapping is unmapped in that case.
+    pub fn into_executable(self) -> Result<ExecutableMmap<'static>, MmapError> {
+        make_executable(self.data(), self.len())?;
+        Ok(ExecutableMmap {
+            mapping: Mapping::Anon(self),
+        })
+    }
+}
+
+/// Changes the whole pages under `data..data + len` to read-only and executable.
+fn make_executable(data: *mut u8, len: usize) -> Result<(), MmapError> {
+    if len == 0 {
+        return Ok(());
+    }
+
+    let (start, len) = page_aligned_range(data as usize, len);
+    protect_memory(
+        start as *mut c_void,
+        len,
+        Protection::READ | Protection::EXECUTE,
+    )
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    /// A function returning 42, i.e. `mov eax, 42; ret` or `mov w0, #42; ret`.
+    #[cfg(target_arch = "x86_64")]
+    const RETURN_42: &[u8] = &[0xB8, 0x2A, 0x00, 0x00, 0x00, 0xC3];
+    #[cfg(target_arch = "aarch64")]
+    const RETURN_42: &[u8] = &[0x40, 0x05, 0x80, 0x52, 0xC0, 0x03, 0x5F, 0xD6];
+
+    #[test]
+    fn can_create_empty_mapping() {
+        let mapping = AnonMmap::new(0).unwrap().into_executable().unwrap();
+        assert!(mapping.is_empty());
+        assert!(mapping.as_slice().is_empty());
+    }
+
+    #[test]
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+    fn can_call_emitted_code() {
+        let mut mapping = AnonMmap::new(4096).unwrap();
+        mapping.as_mut_slice()[..RETURN_42.len()].copy_from_slice(RETURN_42);
+
+        let code = mapping.into_executable().unwrap();
+        assert_eq!(&code.as_slice()[..RETURN_42.len()], RETURN_42);
+
+        let function =
+            unsafe { core::mem::transmute::<*const u8, extern "C" fn() -> i32>(code.data()) };
+        assert_eq!(function(), 42);
+    }
+
+    #[test]
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+    fn can_call_code_loaded_from_file() {
+        use tempfile::NamedTempFile;
+
+        let file = NamedTempFile::new().unwrap();
+        let mut contents = std::vec![0u8; 5000];
+        contents[4100..4100 + RETURN_42.len()].copy_from_slice(RETURN_42);
+        std::fs::write(file.path(), &contents).unwrap();
+
+        let handle = ReadOnlyFileHandle::open(file.path()).unwrap();
+        let code = match ExecutableMmap::new(&handle, 4100, RETURN_42.len()) {
+            Ok(code) => code,
+            // The temporary directory may be on a filesystem mounted `noexec`.
+            #[cfg(unix)]
+            Err(MmapError::FailedToProtect(libc::EACCES | libc::EPERM)) => return,
+            Err(e) => panic!("{e:?}"),
+        };
+        assert_eq!(code.as_slice(), RETURN_42);
+
+        let function =
+            unsafe { core::mem::transmute::<*const u8, extern "C" fn() -> i32>(code.data()) };
+        assert_eq!(function(), 42);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn writing_executable_mapping_faults() {
+        let code = AnonMmap::new(4096).unwrap().into_executable().unwrap();
+
+        match unsafe { libc::fork() } {
+            0 => {
+                unsafe { code.data().cast_mut().write_volatile(0) };
+                unsafe { libc::_exit(0) };
+            }
+            pid => {
+                let mut status = 0;
+                unsafe { libc::waitpid(pid, &mut status, 0) };
+                assert!(libc::WIFSIGNALED(status));
+            }
+        }
+    }
+}
@@ -1,9 +1,15 @@
+pub mod anon;
+pub mod cow;
 pub mod error;
+pub mod exec;
 pub mod readonly;
 pub mod readwrite;
 
+pub use anon::*;
 use bitflags::bitflags;
+pub use cow::*;
 pub use error::*;
+pub use exec::*;
 pub use readonly::*;
 pub use readwrite::*;
 
@@ -49,3 +55,54 @@ bitflags! {
         const RANDOM = 0b100;
     }
 }
+
+/// Widens the range `start..start + len` of a mapping to the page boundaries around it,
+/// as required to change its protection. Returns the aligned start and length.
+pub(crate) fn page_aligned_range(start: usize, len: usize) -> (usize, usize) {
+    let page_size = crate::util::get_page_size() as usize;
+    let aligned_start = start & !(page_size - 1);
+    let aligned_end = (start + len + page_size - 1) & !(page_size - 1);
+    (aligned_start, aligned_end - aligned_start)
+}
+
+/// Checks that `start..start + len` of a mapping covers whole pages, for changing its
+/// protection without touching bytes outside of the range. Both `start` and `mapping_end`
+/// are measured from the page the mapping begins on. The end may only fall inside a page
+/// at `mapping_end`, as the rest of that page belongs to the mapping.
+///
+/// Returns the length of the range, rounded up to whole pages.
+pub(crate) fn whole_page_range(
+    start: usize,
+    len: usize,
+    mapping_end: usize,
+) -> Result<usize, MmapError> {
+    let page_size = crate::util::get_page_size() as usize;
+    let end = start + len;
+    let mask = page_size - 1;
+    if start & mask != 0 || (end & mask != 0 && end != mapping_end) {
+        return Err(MmapError::mapping_failed(
+            "Protect range is not aligned to page boundaries",
+        ));
+    }
+
+    Ok(page_aligned_range(start, len).1)
+}
+
+bitflags! {
+    /// Access allowed to the pages of a memory mapping, as changed with `protect`.
+    ///
+    /// The empty set allows no access at all (`PROT_NONE` / `PAGE_NOACCESS`), so any access
+    /// to the pages faults. Write access implies read access on most platforms.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Protection: u32 {
+        /// The pages may be read.
+        const READ = 0b01;
+        /// The pages may be written.
+        const WRITE = 0b10;
+        /// The pages may be executed as machine code. Normally combined with [`Self::READ`].
+        ///
+        /// The instruction cache is flushed for the range when this is set, so code written to
+        /// it beforehand is seen by the CPU.
+        const EXECUTE = 0b100;
+    }
+}
@@ -1,6 +1,6 @@
 use super::*;
 use crate::handles::ReadOnlyFileHandle;
-use core::slice::from_raw_parts;
+use core::{ffi::c_void, slice::from_raw_parts};
 
 #[cfg(unix)]
 mod unix;
@@ -57,6 +57,24 @@ impl<'a> ReadOnlyMmap<'a> {
         })
     }
 
+    /// Wraps an existing read-only mapping, such as one made by [`ReadWriteMmap::freeze`].
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be the base of a shared, read-only file mapping of `length` bytes
+    /// (including the `offset_adjustment`) which is not owned by anything else.
+    pub(crate) unsafe fn from_raw(
+        ptr: *mut c_void,
+        offset_adjustment: usize,
+        length: usize,
+    ) -> Self {
+        ReadOnlyMmap {
+            inner: ReadOnlyMmapInner::from_raw(ptr, length),
+            offset_adjustment,
+            length,
+        }
+    }
+
     /// Returns a slice of the mapped memory.
     /// The lifetime of the slice is the same as the mapping.
     #[inline]
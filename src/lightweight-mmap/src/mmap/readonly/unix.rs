@@ -17,7 +17,7 @@ impl<'a> ReadOnlyMmapInner<'a> {
         len: usize,
     ) -> Result<(Self, usize, usize), MmapError> {
         let (ptr, offset_adjustment, adjusted_len) =
-            create_mmap(handle.handle().fd(), offset, len, PROT_READ)?;
+            create_mmap(handle.handle().fd(), offset, len, PROT_READ, MAP_SHARED)?;
 
         Ok((
             ReadOnlyMmapInner {
@@ -30,6 +30,20 @@ impl<'a> ReadOnlyMmapInner<'a> {
         ))
     }
 
+    /// Takes ownership of an existing shared mapping of a file, such as one made read-only
+    /// by [`ReadWriteMmap::freeze`].
+    ///
+    /// # Safety
+    ///
+    /// `ptr` and `length` must describe a mapping which is not owned by anything else.
+    pub(crate) unsafe fn from_raw(ptr: *mut c_void, length: usize) -> Self {
+        ReadOnlyMmapInner {
+            ptr,
+            length,
+            _phantom: PhantomData,
+        }
+    }
+
     #[inline]
     pub fn data(&self) -> *mut c_void {
         self.ptr
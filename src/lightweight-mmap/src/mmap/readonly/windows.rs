@@ -34,6 +34,19 @@ impl<'a> ReadOnlyMmapInner<'a> {
         ))
     }
 
+    /// Takes ownership of an existing view of a file, such as one made read-only
+    /// by [`ReadWriteMmap::freeze`].
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be the base of a view which is not owned by anything else.
+    pub(crate) unsafe fn from_raw(ptr: *mut c_void, _length: usize) -> Self {
+        ReadOnlyMmapInner {
+            ptr,
+            _phantom: PhantomData,
+        }
+    }
+
     #[inline]
     pub fn data(&self) -> *mut c_void {
         self.ptr
@@ -1,6 +1,7 @@
 use super::*;
 use crate::handles::ReadWriteFileHandle;
-use core::slice::from_raw_parts;
+use crate::util::get_allocation_granularity;
+use core::{ffi::c_void, slice::from_raw_parts};
 
 #[cfg(unix)]
 mod unix;
@@ -122,6 +123,136 @@ impl<'a> ReadWriteMmap<'a> {
             advise_memory(self.inner.data(), self.length, advice)
         }
     }
+
+    /// Flushes outstanding changes in the mapping to the file, and waits until they are written.
+    ///
+    /// Uses `msync` with `MS_SYNC` on Unix, and `FlushViewOfFile` followed by `FlushFileBuffers`
+    /// on Windows.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`MmapError::FailedToFlush`] if the changes cannot be flushed.
+    pub fn flush(&self) -> Result<(), MmapError> {
+        self.flush_impl(0, self.len(), true)
+    }
+
+    /// Starts flushing outstanding changes in the mapping to the file, without waiting
+    /// for the write to complete.
+    ///
+    /// Uses `msync` with `MS_ASYNC` on Unix, and `FlushViewOfFile` on Windows.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`MmapError::FailedToFlush`] if the flush cannot be started.
+    pub fn flush_async(&self) -> Result<(), MmapError> {
+        self.flush_impl(0, self.len(), false)
+    }
+
+    /// Flushes outstanding changes in part of the mapping to the file, and waits until
+    /// they are written.
+    ///
+    /// The range is widened to the start of its page as required by the OS, so slightly
+    /// more data than requested may be flushed.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The offset of the range, relative to the start of this mapping (see [`Self::as_slice`]).
+    /// * `len` - The length of the range in bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`MmapError`] if the range is outside of the mapping, or the changes cannot be flushed.
+    pub fn flush_range(&self, offset: usize, len: usize) -> Result<(), MmapError> {
+        self.flush_impl(offset, len, true)
+    }
+
+    /// Changes the access allowed to part of the mapping.
+    ///
+    /// Protection is changed a whole page at a time, so the range must start on a page
+    /// boundary (i.e. `data() + offset` is a multiple of the page size), and end on one too,
+    /// unless it extends to the end of the mapping.
+    ///
+    /// Uses `mprotect` on Unix and `VirtualProtect` on Windows.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The offset of the range, relative to the start of this mapping (see [`Self::as_slice`]).
+    /// * `len` - The length of the range in bytes.
+    /// * `protection` - The access to allow. [`Protection::empty`] makes any access fault.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`MmapError`] if the range is outside of the mapping or not aligned to page
+    /// boundaries, or the protection cannot be changed.
+    ///
+    /// # Safety
+    ///
+    /// Any access to a page which no longer allows it faults the process (`SIGSEGV` on Unix,
+    /// an access violation on Windows). The caller must ensure that nothing, including slices
+    /// previously returned by [`Self::as_slice`] and [`Self::as_mut_slice`], accesses the
+    /// range in a way it no longer allows.
+    pub unsafe fn protect(
+        &self,
+        offset: usize,
+        len: usize,
+        protection: Protection,
+    ) -> Result<(), MmapError> {
+        if offset.checked_add(len).is_none_or(|end| end > self.len()) {
+            return Err(MmapError::mapping_failed("Protect range is out of bounds"));
+        }
+
+        if len == 0 {
+            return Ok(());
+        }
+
+        // The mapping itself starts on a page boundary `offset_adjustment` bytes before `data()`.
+        let start = self.offset_adjustment + offset;
+        let len = whole_page_range(start, len, self.offset_adjustment + self.len())?;
+        protect_memory(
+            (self.inner.data() as *mut u8).add(start) as *mut c_void,
+            len,
+            protection,
+        )
+    }
+
+    /// Makes the whole mapping read-only in place, and converts it into a [`ReadOnlyMmap`].
+    ///
+    /// The contents are not copied or remapped; any write to the memory afterwards faults.
+    /// Changes already made are kept, but a read-only mapping cannot be flushed, so call
+    /// [`Self::flush`] first if they need to reach the disk.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`MmapError::FailedToProtect`] if the protection cannot be changed.
+    /// The mapping is unmapped in that case.
+    pub fn freeze(self) -> Result<ReadOnlyMmap<'a>, MmapError> {
+        if !self.is_empty() {
+            protect_memory(self.inner.data(), self.length, Protection::READ)?;
+        }
+
+        let ReadWriteMmap {
+            inner,
+            offset_adjustment,
+            length,
+        } = self;
+        Ok(unsafe { ReadOnlyMmap::from_raw(inner.into_raw(), offset_adjustment, length) })
+    }
+
+    fn flush_impl(&self, offset: usize, len: usize, sync: bool) -> Result<(), MmapError> {
+        if offset.checked_add(len).is_none_or(|end| end > self.len()) {
+            return Err(MmapError::mapping_failed("Flush range is out of bounds"));
+        }
+
+        if len == 0 {
+            return Ok(());
+        }
+
+        // The mapping itself starts on a page boundary `offset_adjustment` bytes before `data()`.
+        let start = self.offset_adjustment + offset;
+        let aligned_start = start & !(get_allocation_granularity() as usize - 1);
+        self.inner
+            .flush(aligned_start, start + len - aligned_start, sync)
+    }
 }
 
 #[cfg(all(test, feature = "std"))]
@@ -319,4 +450,103 @@ mod tests {
         assert!(!mapping.data().is_null());
         assert_eq!(mapping.as_slice().len(), 0);
     }
+
+    #[test]
+    fn can_flush_mapping() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(&vec![0u8; 8192]).unwrap();
+        file.flush().unwrap();
+
+        let handle = ReadWriteFileHandle::open(file.path().to_str().unwrap()).unwrap();
+        let mut mapping = ReadWriteMmap::new(&handle, 4099, 1000).unwrap();
+        mapping.as_mut_slice().fill(0xFF);
+
+        mapping.flush().unwrap();
+        mapping.flush_async().unwrap();
+        mapping.flush_range(1, 998).unwrap();
+        mapping.flush_range(1000, 0).unwrap();
+        assert!(mapping.flush_range(1, 1000).is_err());
+        assert!(mapping.flush_range(usize::MAX, 2).is_err());
+    }
+
+    #[test]
+    fn can_protect_and_freeze_mapping() {
+        let page = crate::util::get_page_size() as usize;
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(&vec![0u8; 3 * page]).unwrap();
+        file.flush().unwrap();
+
+        // The mapping starts 3 bytes into a page, so its first page boundary is at `aligned`.
+        let handle = ReadWriteFileHandle::open(file.path().to_str().unwrap()).unwrap();
+        let mut mapping = ReadWriteMmap::new(&handle, 3, 2 * page).unwrap();
+        let aligned = page - 3;
+        mapping.as_mut_slice().fill(0xAA);
+
+        unsafe {
+            assert!(mapping.protect(0, 100, Protection::empty()).is_err());
+            assert!(mapping.protect(aligned, 100, Protection::empty()).is_err());
+            mapping.protect(aligned, page, Protection::empty()).unwrap();
+            assert!(mapping.as_slice()[..aligned].iter().all(|&x| x == 0xAA));
+            mapping
+                .protect(aligned, 2 * page - aligned, Protection::READ)
+                .unwrap();
+            assert!(mapping
+                .protect(aligned, 2 * page, Protection::READ)
+                .is_err());
+            assert!(mapping.protect(usize::MAX, 2, Protection::READ).is_err());
+            mapping
+                .protect(
+                    aligned,
+                    2 * page - aligned,
+                    Protection::READ | Protection::WRITE,
+                )
+                .unwrap();
+        }
+        mapping.as_mut_slice()[..5].copy_from_slice(b"HELLO");
+
+        let frozen = mapping.freeze().unwrap();
+        assert_eq!(frozen.len(), 2 * page);
+        assert_eq!(&frozen.as_slice()[..6], b"HELLO\xAA");
+        drop(frozen);
+
+        let content = std::fs::read(file.path()).unwrap();
+        assert_eq!(&content[3..8], b"HELLO");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn writing_frozen_mapping_faults() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"Hello, World!").unwrap();
+        file.flush().unwrap();
+
+        let handle = ReadWriteFileHandle::open(file.path().to_str().unwrap()).unwrap();
+        let frozen = ReadWriteMmap::new(&handle, 0, 13)
+            .unwrap()
+            .freeze()
+            .unwrap();
+
+        match unsafe { libc::fork() } {
+            0 => {
+                unsafe { frozen.data().cast_mut().write_volatile(b'J') };
+                unsafe { libc::_exit(0) };
+            }
+            pid => {
+                let mut status = 0;
+                unsafe { libc::waitpid(pid, &mut status, 0) };
+                assert!(libc::WIFSIGNALED(status));
+                assert_eq!(frozen.as_slice(), b"Hello, World!");
+            }
+        }
+    }
+
+    #[test]
+    fn can_flush_empty_mapping() {
+        let file = NamedTempFile::new().unwrap();
+        let handle = ReadWriteFileHandle::open(file.path().to_str().unwrap()).unwrap();
+
+        let mapping = ReadWriteMmap::new(&handle, 0, 0).unwrap();
+        mapping.flush().unwrap();
+        mapping.flush_async().unwrap();
+    }
 }
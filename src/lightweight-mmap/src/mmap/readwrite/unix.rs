@@ -1,5 +1,5 @@
 use super::*;
-use core::marker::PhantomData;
+use core::{marker::PhantomData, mem::ManuallyDrop};
 use libc::*;
 use unix_common::create_mmap;
 
@@ -16,8 +16,13 @@ impl<'a> ReadWriteMmapInner<'a> {
         offset: u64,
         len: usize,
     ) -> Result<(Self, usize, usize), MmapError> {
-        let (ptr, offset_adjustment, adjusted_len) =
-            create_mmap(handle.handle().fd(), offset, len, PROT_READ | PROT_WRITE)?;
+        let (ptr, offset_adjustment, adjusted_len) = create_mmap(
+            handle.handle().fd(),
+            offset,
+            len,
+            PROT_READ | PROT_WRITE,
+            MAP_SHARED,
+        )?;
 
         Ok((
             ReadWriteMmapInner {
@@ -34,6 +39,28 @@ impl<'a> ReadWriteMmapInner<'a> {
     pub fn data(&self) -> *mut c_void {
         self.ptr
     }
+
+    /// Releases ownership of the mapping without unmapping it, returning its base address.
+    pub(crate) fn into_raw(self) -> *mut c_void {
+        ManuallyDrop::new(self).ptr
+    }
+
+    /// Flushes a page aligned range of the mapping to the file with `msync`.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - Offset of the range from the start of the mapping. Must be page aligned.
+    /// * `len` - Length of the range.
+    /// * `sync` - Whether to wait for the write to complete (`MS_SYNC`) or only start it (`MS_ASYNC`).
+    pub(crate) fn flush(&self, offset: usize, len: usize, sync: bool) -> Result<(), MmapError> {
+        let flags = if sync { MS_SYNC } else { MS_ASYNC };
+        let ret = unsafe { msync((self.ptr as *mut u8).add(offset) as *mut c_void, len, flags) };
+        if ret != 0 {
+            return Err(MmapError::FailedToFlush(errno::errno().0));
+        }
+
+        Ok(())
+    }
 }
 
 impl Drop for ReadWriteMmapInner<'_> {
@@ -1,10 +1,15 @@
 use super::*;
-use core::{ffi::c_void, marker::PhantomData};
-use windows_sys::Win32::System::Memory::*;
+use core::{ffi::c_void, marker::PhantomData, mem::ManuallyDrop};
+use windows_sys::Win32::{
+    Foundation::{GetLastError, HANDLE},
+    Storage::FileSystem::FlushFileBuffers,
+    System::Memory::*,
+};
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub(crate) struct ReadWriteMmapInner<'a> {
     ptr: *mut c_void,
+    file: HANDLE,
     _phantom: PhantomData<&'a ReadWriteFileHandle>,
 }
 
@@ -27,6 +32,7 @@ impl<'a> ReadWriteMmapInner<'a> {
         Ok((
             ReadWriteMmapInner {
                 ptr,
+                file: inner.handle(),
                 _phantom: PhantomData,
             },
             offset_adjustment,
@@ -38,6 +44,33 @@ impl<'a> ReadWriteMmapInner<'a> {
     pub fn data(&self) -> *mut c_void {
         self.ptr
     }
+
+    /// Releases ownership of the mapping without unmapping it, returning its base address.
+    pub(crate) fn into_raw(self) -> *mut c_void {
+        ManuallyDrop::new(self).ptr
+    }
+
+    /// Flushes a range of the view to the file with `FlushViewOfFile`.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - Offset of the range from the start of the view.
+    /// * `len` - Length of the range.
+    /// * `sync` - Whether to also wait for the file to be flushed to disk (`FlushFileBuffers`).
+    pub(crate) fn flush(&self, offset: usize, len: usize, sync: bool) -> Result<(), MmapError> {
+        unsafe {
+            let addr = (self.ptr as *const u8).add(offset) as *const c_void;
+            if FlushViewOfFile(addr, len) == 0 {
+                return Err(MmapError::FailedToFlush(GetLastError()));
+            }
+
+            if sync && FlushFileBuffers(self.file) == 0 {
+                return Err(MmapError::FailedToFlush(GetLastError()));
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl Drop for ReadWriteMmapInner<'_> {
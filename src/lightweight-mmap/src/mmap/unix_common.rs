@@ -8,6 +8,7 @@ pub(crate) fn create_mmap(
     offset: u64,
     len: usize,
     protection: c_int,
+    flags: c_int,
 ) -> Result<(*mut c_void, usize, usize), MmapError> {
     // Special case for zero length
     if len == 0 {
@@ -29,7 +30,7 @@ pub(crate) fn create_mmap(
                 ptr::null_mut(),
                 adjusted_len,
                 protection,
-                MAP_SHARED,
+                flags,
                 fd,
                 aligned_offset as libc::off64_t,
             )
@@ -40,7 +41,7 @@ pub(crate) fn create_mmap(
                 ptr::null_mut(),
                 adjusted_len,
                 protection,
-                MAP_SHARED,
+                flags,
                 fd,
                 aligned_offset as libc::off_t,
             )
@@ -54,6 +55,137 @@ pub(crate) fn create_mmap(
     Ok((ptr, offset_adjustment as usize, adjusted_len))
 }
 
+/// Maps `len` bytes of zeroed memory which is not backed by a file.
+///
+/// `flags` should be `MAP_PRIVATE`, or `MAP_SHARED` to share the memory with child processes.
+pub(crate) fn create_anonymous_mmap(len: usize, flags: c_int) -> Result<*mut c_void, MmapError> {
+    // Special case for zero length
+    if len == 0 {
+        return Ok(NonNull::dangling().as_ptr());
+    }
+
+    let ptr = unsafe {
+        mmap(
+            ptr::null_mut(),
+            len,
+            PROT_READ | PROT_WRITE,
+            flags | MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+
+    if ptr == MAP_FAILED {
+        return Err(MmapError::failed_to_map_memory_unix(errno::errno().0));
+    }
+
+    Ok(ptr)
+}
+
+/// Changes the access allowed to a page aligned range of memory with `mprotect`.
+pub(crate) fn protect_memory(
+    addr: *mut c_void,
+    len: usize,
+    protection: Protection,
+) -> Result<(), MmapError> {
+    let mut prot = PROT_NONE;
+    if protection.contains(Protection::READ) {
+        prot |= PROT_READ;
+    }
+    if protection.contains(Protection::WRITE) {
+        prot |= PROT_WRITE;
+    }
+    if protection.contains(Protection::EXECUTE) {
+        prot |= PROT_EXEC;
+    }
+
+    if unsafe { mprotect(addr, len, prot) } != 0 {
+        return Err(MmapError::FailedToProtect(errno::errno().0));
+    }
+
+    if protection.contains(Protection::EXECUTE) {
+        flush_instruction_cache(addr, len);
+    }
+
+    Ok(())
+}
+
+/// Makes code written to a range of memory visible to instruction fetches, on architectures
+/// where the instruction cache is not kept coherent with data writes (x86 is).
+#[cfg_attr(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    allow(unused_variables)
+)]
+pub(crate) fn flush_instruction_cache(addr: *mut c_void, len: usize) {
+    #[cfg(all(target_arch = "aarch64", target_vendor = "apple"))]
+    unsafe {
+        extern "C" {
+            fn sys_icache_invalidate(start: *mut c_void, len: usize);
+        }
+        sys_icache_invalidate(addr, len);
+    }
+
+    #[cfg(all(target_arch = "aarch64", not(target_vendor = "apple")))]
+    unsafe {
+        flush_instruction_cache_aarch64(addr as usize, len);
+    }
+
+    #[cfg(all(target_arch = "arm", any(target_os = "linux", target_os = "android")))]
+    unsafe {
+        // __ARM_NR_cacheflush, a private ARM syscall which is not exported by libc.
+        const ARM_NR_CACHEFLUSH: c_long = 0x0f0002;
+        let _ = syscall(ARM_NR_CACHEFLUSH, addr, (addr as *mut u8).add(len), 0);
+    }
+
+    #[cfg(not(any(
+        target_arch = "x86",
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        all(target_arch = "arm", any(target_os = "linux", target_os = "android"))
+    )))]
+    unsafe {
+        // Provided by libgcc / compiler-rt.
+        extern "C" {
+            fn __clear_cache(start: *mut c_char, end: *mut c_char);
+        }
+        __clear_cache(addr as *mut c_char, (addr as *mut c_char).add(len));
+    }
+}
+
+/// Cleans the data cache and invalidates the instruction cache for a range, one cache line
+/// at a time, like compiler-rt's `__clear_cache`. EL0 may do this on Linux and Windows.
+#[cfg(all(target_arch = "aarch64", not(target_vendor = "apple")))]
+unsafe fn flush_instruction_cache_aarch64(start: usize, len: usize) {
+    use core::arch::asm;
+
+    let end = start + len;
+    let ctr_el0: u64;
+    asm!("mrs {}, ctr_el0", out(reg) ctr_el0, options(nomem, nostack, preserves_flags));
+
+    // CTR_EL0.IDC: cleaning the data cache is not required for instruction coherence.
+    if ctr_el0 & (1 << 28) == 0 {
+        let line = 4usize << ((ctr_el0 >> 16) & 0xF);
+        let mut addr = start & !(line - 1);
+        while addr < end {
+            asm!("dc cvau, {}", in(reg) addr, options(nostack, preserves_flags));
+            addr += line;
+        }
+    }
+    asm!("dsb ish", options(nostack, preserves_flags));
+
+    // CTR_EL0.DIC: invalidating the instruction cache is not required for coherence.
+    if ctr_el0 & (1 << 29) == 0 {
+        let line = 4usize << (ctr_el0 & 0xF);
+        let mut addr = start & !(line - 1);
+        while addr < end {
+            asm!("ic ivau, {}", in(reg) addr, options(nostack, preserves_flags));
+            addr += line;
+        }
+        asm!("dsb ish", options(nostack, preserves_flags));
+    }
+    asm!("isb", options(nostack, preserves_flags));
+}
+
 #[cfg(unix)]
 pub(crate) fn advise_memory(addr: *mut libc::c_void, len: usize, advice: MemoryAdvice) {
     // Check each flag and make the corresponding madvise call
@@ -70,3 +202,12 @@ pub(crate) fn advise_memory(addr: *mut libc::c_void, len: usize, advice: MemoryA
         }
     }
 }
+
+/// Releases the pages under `addr..addr + len` with `MADV_DONTNEED`, which may discard the
+/// contents of private mappings. Callers must hold the mapping mutably.
+pub(crate) fn discard_memory(addr: *mut c_void, len: usize) {
+    // Ignore any errors as this is just a hint
+    unsafe {
+        let _ = madvise(addr, len, MADV_DONTNEED);
+    }
+}
@@ -3,7 +3,9 @@ use super::*;
 use crate::util::get_allocation_granularity;
 use core::ptr::{null, NonNull};
 use core::{ffi::c_void, ptr::null_mut};
-use windows_sys::Win32::System::Threading::GetCurrentProcess;
+use windows_sys::Win32::System::{
+    Diagnostics::Debug::FlushInstructionCache, Threading::GetCurrentProcess,
+};
 use windows_sys::Win32::{Foundation::*, System::Memory::*};
 
 pub(crate) fn create_view(
@@ -56,6 +58,99 @@ pub(crate) fn create_view(
     Ok((ptr, offset_adjustment as usize, adjusted_len))
 }
 
+/// Allocates `len` bytes of zeroed memory which is not backed by a file.
+///
+/// Private memory is allocated with `VirtualAlloc`, and returned with a null mapping handle.
+/// Shared memory is a view of a pagefile-backed section, returned with the section's handle.
+pub(crate) fn create_anonymous_view(
+    len: usize,
+    shared: bool,
+) -> Result<(*mut c_void, HANDLE), MmapError> {
+    // Special case for zero length
+    if len == 0 {
+        return Ok((NonNull::dangling().as_ptr(), null_mut()));
+    }
+
+    if !shared {
+        let ptr = unsafe { VirtualAlloc(null(), len, MEM_RESERVE | MEM_COMMIT, PAGE_READWRITE) };
+        if ptr.is_null() {
+            return Err(MmapError::failed_to_map_memory(unsafe { GetLastError() }));
+        }
+
+        return Ok((ptr, null_mut()));
+    }
+
+    unsafe {
+        let mapping = CreateFileMappingW(
+            INVALID_HANDLE_VALUE,
+            null_mut(),
+            PAGE_READWRITE,
+            ((len as u64) >> 32) as u32,
+            len as u32,
+            null(),
+        );
+
+        if mapping.is_null() {
+            return Err(MmapError::failed_to_map_memory(GetLastError()));
+        }
+
+        let ptr = MapViewOfFile(mapping, FILE_MAP_WRITE, 0, 0, len).Value;
+        if ptr.is_null() {
+            let error = GetLastError();
+            CloseHandle(mapping);
+            return Err(MmapError::failed_to_map_memory(error));
+        }
+
+        Ok((ptr, mapping))
+    }
+}
+
+/// Changes the access allowed to a page aligned range of memory with `VirtualProtect`.
+pub(crate) fn protect_memory(
+    addr: *mut c_void,
+    len: usize,
+    protection: Protection,
+) -> Result<(), MmapError> {
+    // Windows has no write-only pages, so write access always includes read access.
+    let execute = protection.contains(Protection::EXECUTE);
+    let new_protect = if protection.contains(Protection::WRITE) {
+        if execute {
+            PAGE_EXECUTE_READWRITE
+        } else {
+            PAGE_READWRITE
+        }
+    } else if protection.contains(Protection::READ) {
+        if execute {
+            PAGE_EXECUTE_READ
+        } else {
+            PAGE_READONLY
+        }
+    } else if execute {
+        PAGE_EXECUTE
+    } else {
+        PAGE_NOACCESS
+    };
+
+    let mut old_protect = 0;
+    if unsafe { VirtualProtect(addr, len, new_protect, &mut old_protect) } == 0 {
+        return Err(MmapError::FailedToProtect(unsafe { GetLastError() }));
+    }
+
+    if execute {
+        flush_instruction_cache(addr, len);
+    }
+
+    Ok(())
+}
+
+/// Makes code written to a range of memory visible to instruction fetches with
+/// `FlushInstructionCache`. This is required on ARM64, and cheap elsewhere.
+pub(crate) fn flush_instruction_cache(addr: *mut c_void, len: usize) {
+    unsafe {
+        let _ = FlushInstructionCache(GetCurrentProcess(), addr, len);
+    }
+}
+
 pub(crate) fn advise_memory(addr: *mut core::ffi::c_void, len: usize, advice: MemoryAdvice) {
     // Windows only supports prefetching (similar to MADV_WILLNEED)
     // Other advice types are ignored
@@ -70,3 +165,12 @@ pub(crate) fn advise_memory(addr: *mut core::ffi::c_void, len: usize, advice: Me
         }
     }
 }
+
+/// Discards the pages under `addr..addr + len` with `DiscardVirtualMemory`, leaving their
+/// contents unspecified. Callers must hold the mapping mutably.
+pub(crate) fn discard_memory(addr: *mut c_void, len: usize) {
+    // Fails for views of files; ignored, as this is just a hint.
+    unsafe {
+        let _ = DiscardVirtualMemory(addr, len);
+    }
+}
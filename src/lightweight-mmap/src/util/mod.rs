@@ -15,6 +15,10 @@ use core::sync::atomic::{AtomicU32, Ordering};
 /// Cached system allocation granularity.
 static SYSTEM_ALLOCATION_GRANULARITY: AtomicU32 = AtomicU32::new(0);
 
+/// Cached system page size.
+#[cfg(feature = "mmap")]
+static SYSTEM_PAGE_SIZE: AtomicU32 = AtomicU32::new(0);
+
 /// Returns the system's memory allocation granularity.
 ///
 /// This function caches the allocation granularity after the first call to avoid
@@ -33,3 +37,23 @@ pub fn get_allocation_granularity() -> u32 {
     SYSTEM_ALLOCATION_GRANULARITY.store(size, Ordering::Relaxed);
     size
 }
+
+/// Returns the system's memory page size.
+///
+/// This is the granularity of memory protection changes. On Unix it is the same as
+/// the allocation granularity; on Windows it is usually smaller (4K rather than 64K).
+///
+/// # Returns
+///
+/// The system page size in bytes.
+#[cfg(feature = "mmap")]
+pub fn get_page_size() -> u32 {
+    let cached = SYSTEM_PAGE_SIZE.load(Ordering::Relaxed);
+    if cached != 0 {
+        return cached;
+    }
+
+    let size = unsafe { query_page_size() };
+    SYSTEM_PAGE_SIZE.store(size, Ordering::Relaxed);
+    size
+}
@@ -8,3 +8,13 @@ use libc::*;
 pub unsafe fn query_allocation_granularity() -> u32 {
     sysconf(_SC_PAGESIZE) as u32
 }
+
+/// Query the size of a memory page on Unix systems.
+///
+/// # Safety
+///
+/// This function is unsafe because it calls the raw sysconf syscall.
+#[cfg(feature = "mmap")]
+pub unsafe fn query_page_size() -> u32 {
+    sysconf(_SC_PAGESIZE) as u32
+}
@@ -64,3 +64,16 @@ pub unsafe fn query_allocation_granularity() -> u32 {
     GetSystemInfo(&mut system_info);
     system_info.dwAllocationGranularity
 }
+
+/// Query the size of a memory page on Windows systems, which is the granularity of
+/// protection changes.
+///
+/// # Safety
+///
+/// This function is unsafe because it calls the raw [`GetSystemInfo`] syscall.
+#[cfg(feature = "mmap")]
+pub unsafe fn query_page_size() -> u32 {
+    let mut system_info: SYSTEM_INFO = zeroed();
+    GetSystemInfo(&mut system_info);
+    system_info.dwPageSize
+}